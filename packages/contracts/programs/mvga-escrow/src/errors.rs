@@ -4,10 +4,10 @@ use anchor_lang::prelude::*;
 pub enum EscrowError {
     #[msg("Invalid escrow status for this operation")]
     InvalidStatus,
-    #[msg("Unauthorized: only the seller can perform this action")]
-    UnauthorizedSeller,
-    #[msg("Unauthorized: only the buyer can perform this action")]
-    UnauthorizedBuyer,
+    #[msg("Unauthorized: only the depositor can perform this action")]
+    UnauthorizedDepositor,
+    #[msg("Unauthorized: only the beneficiary can perform this action")]
+    UnauthorizedBeneficiary,
     #[msg("Unauthorized: only the admin can perform this action")]
     UnauthorizedAdmin,
     #[msg("Escrow has not timed out yet")]
@@ -24,12 +24,120 @@ pub enum EscrowError {
     TimeoutOverflow,
     #[msg("Timeout exceeds maximum allowed (30 days)")]
     TimeoutTooLong,
-    #[msg("Buyer and seller cannot be the same account")]
-    BuyerCannotBeSeller,
-    #[msg("Admin cannot be the buyer or seller")]
+    #[msg("Depositor and beneficiary cannot be the same account")]
+    BeneficiaryCannotBeDepositor,
+    #[msg("Admin cannot be the depositor or beneficiary")]
     InvalidAdmin,
     #[msg("Token mint must not have a freeze authority")]
     MintHasFreezeAuthority,
     #[msg("Invalid token program")]
     InvalidTokenProgram,
+    #[msg("Milestone escrows need between 1 and 8 milestones")]
+    InvalidMilestoneCount,
+    #[msg("Milestone amounts must be non-zero and sum to the escrow amount")]
+    MilestoneAmountMismatch,
+    #[msg("Milestone deadline must be in the future")]
+    InvalidMilestoneDeadline,
+    #[msg("Operation not supported for this escrow mode")]
+    InvalidMode,
+    #[msg("Vesting end must be after start and in the future")]
+    InvalidVestingSchedule,
+    #[msg("No vested tokens available to claim")]
+    NothingToClaim,
+    #[msg("Preimage does not match the escrow hashlock")]
+    InvalidPreimage,
+    #[msg("Swap legs must use different mints")]
+    SwapMintsMustDiffer,
+    #[msg("Unauthorized: only the designated taker can take this swap")]
+    UnauthorizedTaker,
+    #[msg("Unauthorized: only the maker can perform this action")]
+    UnauthorizedMaker,
+    #[msg("Fill limits must be non-zero with min_fill <= max_fill")]
+    InvalidFillLimits,
+    #[msg("Fill amount is outside the offer's min/max range")]
+    FillOutOfRange,
+    #[msg("Not enough unreserved liquidity in the offer")]
+    InsufficientLiquidity,
+    #[msg("Payment method not accepted by this offer")]
+    PaymentMethodNotAllowed,
+    #[msg("Missing or invalid ed25519 signature instruction")]
+    InvalidSignature,
+    #[msg("Signed order has expired")]
+    OrderExpired,
+    #[msg("Release voucher does not match this escrow")]
+    VoucherMismatch,
+    #[msg("Release voucher has expired")]
+    VoucherExpired,
+    #[msg("Escrow did not opt in to attested release")]
+    AttestedReleaseNotEnabled,
+    #[msg("Payment attestation does not match this escrow")]
+    AttestationMismatch,
+    #[msg("Session key does not grant this action to the signer")]
+    InvalidSessionKey,
+    #[msg("Session key has expired")]
+    SessionExpired,
+    #[msg("Session expiry must be in the future and within 30 days")]
+    InvalidSessionExpiry,
+    #[msg("Release exceeds the session spend cap")]
+    SessionSpendCapExceeded,
+    #[msg("Rent payer does not match the escrow")]
+    InvalidRentPayer,
+    #[msg("Rent sponsor pool cannot cover this escrow's rent")]
+    RentSponsorDepleted,
+    #[msg("Destination token account is frozen")]
+    DestinationFrozen,
+    #[msg("Destination token account does not match the one chosen for this escrow")]
+    InvalidDestination,
+    #[msg("New timeout must not be shorter than the current one")]
+    TimeoutNotExtended,
+    #[msg("Payment reference has already been updated")]
+    PaymentReferenceLocked,
+    #[msg("Escrow account is already on the current layout")]
+    AlreadyMigrated,
+    #[msg("Signer does not hold the role this action requires")]
+    MissingRole,
+    #[msg("The config admin role can only be handed over, not revoked")]
+    ConfigAdminNotRevocable,
+    #[msg("Role is not assigned")]
+    RoleNotAssigned,
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Program is not paused")]
+    NotPaused,
+    #[msg("This change must go through the config change timelock")]
+    TimelockRequired,
+    #[msg("Config change delay has not elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("Config delay must be between zero and 30 days")]
+    ConfigDelayTooLong,
+    #[msg("Emergency grace period must be between one second and 90 days")]
+    InvalidGracePeriod,
+    #[msg("Program has not been paused for the emergency grace period")]
+    EmergencyExitClosed,
+    #[msg("Wallet is on the denylist")]
+    WalletDenylisted,
+    #[msg("Denylisted wallet is not a party to this escrow")]
+    WalletNotInvolved,
+    #[msg("Trade amount requires a valid KYC attestation for both parties")]
+    KycRequired,
+    #[msg("KYC attestation expiry must be in the future")]
+    InvalidKycExpiry,
+    #[msg("Trade would exceed the wallet's rolling 24h volume limit")]
+    DailyVolumeExceeded,
+    #[msg("Trade would exceed the wallet's rolling 30-day volume limit")]
+    MonthlyVolumeExceeded,
+    #[msg("Escrow would push the mint's total value locked above its cap")]
+    TvlCapExceeded,
+    #[msg("Platform fee must be at most 5% and each referral share at most half of it")]
+    InvalidFeeConfig,
+    #[msg("Referrer account does not match the escrow's referrer or is a party to it")]
+    InvalidReferrer,
+    #[msg("Fee recipient's token account is missing or wrong")]
+    InvalidFeeAccount,
+    #[msg("The mint caps this wallet's volume, so its volume tracker must be passed")]
+    VolumeTrackerRequired,
+    #[msg("Hashlock has timed out — the preimage can no longer claim it")]
+    HashlockExpired,
+    #[msg("Only the beneficiary's own wallet can change the payout account")]
+    PayoutChangeNotAllowed,
 }
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, Denylisted, Role, WalletDenylisted};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToDenylist<'info> {
+    /// Config admin
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = config_admin,
+        space = 8 + Denylisted::INIT_SPACE,
+        seeds = [b"denylist", wallet.as_ref()],
+        bump,
+    )]
+    pub denylisted: Account<'info, Denylisted>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_add_to_denylist(ctx: Context<AddToDenylist>, wallet: Pubkey) -> Result<()> {
+    let denylisted = &mut ctx.accounts.denylisted;
+    denylisted.wallet = wallet;
+    denylisted.added_by = ctx.accounts.config_admin.key();
+    denylisted.added_at = Clock::get()?.unix_timestamp;
+    denylisted.bump = ctx.bumps.denylisted;
+
+    emit!(WalletDenylisted {
+        wallet,
+        added_by: denylisted.added_by,
+    });
+
+    msg!("Wallet denylisted: {}", wallet);
+    Ok(())
+}
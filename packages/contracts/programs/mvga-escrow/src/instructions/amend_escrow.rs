@@ -0,0 +1,285 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{
+    Config, EscrowAmended, EscrowMode, EscrowState, EscrowStatus, GlobalStats, KycAttestation,
+    KycAttestor, MintPolicy, VolumeTracker,
+};
+
+use super::initialize::{record_volume, PartyVolume};
+
+#[derive(Accounts)]
+pub struct AmendEscrow<'info> {
+    /// Depositor agreeing to the new terms — funds any top-up, and the rent of volume
+    /// trackers it creates
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// Beneficiary agreeing to the new terms
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — top-ups are blocked while paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = escrow_state.depositor == depositor.key() @ EscrowError::UnauthorizedDepositor,
+        constraint = escrow_state.beneficiary == beneficiary.key() @ EscrowError::UnauthorizedBeneficiary,
+        constraint = escrow_state.status == EscrowStatus::Locked @ EscrowError::InvalidStatus,
+        constraint = escrow_state.mode == EscrowMode::Standard @ EscrowError::InvalidMode,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Vault holding escrowed tokens
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Depositor's token account — source of a top-up, and where a reduction goes unless
+    /// the depositor chose a refund account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = depositor,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Refund account the depositor chose at init or through `set_refund_account`
+    #[account(mut)]
+    pub refund_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Depositor's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", depositor.key().as_ref()],
+        bump,
+        constraint = depositor_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub depositor_denylist: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", beneficiary.key().as_ref()],
+        bump,
+        constraint = beneficiary_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub beneficiary_denylist: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: The mint's policy PDA — may not exist, in which case the mint is ungated
+    #[account(
+        seeds = [b"mint_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    /// Depositor's KYC attestation — required when a top-up lifts the amount above the
+    /// mint's threshold
+    #[account(
+        seeds = [b"kyc", depositor.key().as_ref()],
+        bump = depositor_kyc.bump,
+    )]
+    pub depositor_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `depositor_kyc`
+    #[account(
+        seeds = [b"kyc_attestor", depositor_kyc_attestor.authority.as_ref()],
+        bump = depositor_kyc_attestor.bump,
+    )]
+    pub depositor_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Beneficiary's KYC attestation — required when a top-up lifts the amount above the
+    /// mint's threshold
+    #[account(
+        seeds = [b"kyc", beneficiary.key().as_ref()],
+        bump = beneficiary_kyc.bump,
+    )]
+    pub beneficiary_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `beneficiary_kyc`
+    #[account(
+        seeds = [b"kyc_attestor", beneficiary_kyc_attestor.authority.as_ref()],
+        bump = beneficiary_kyc_attestor.bump,
+    )]
+    pub beneficiary_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Depositor's rolling volume in this mint — required for a top-up while the mint caps
+    /// their volume, omit otherwise
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", depositor.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub depositor_volume: Option<Box<Account<'info, VolumeTracker>>>,
+
+    /// Beneficiary's rolling volume in this mint — required for a top-up while the mint caps
+    /// their volume, omit otherwise
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", beneficiary.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub beneficiary_volume: Option<Box<Account<'info, VolumeTracker>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_amend_escrow(
+    ctx: Context<AmendEscrow>,
+    new_amount: u64,
+    new_timeout_seconds: Option<u64>,
+) -> Result<()> {
+    let escrow = &ctx.accounts.escrow_state;
+    let old_amount = escrow.amount;
+    let old_timeout_seconds = escrow.timeout_seconds;
+    let timeout_seconds = new_timeout_seconds.unwrap_or(old_timeout_seconds);
+
+    require!(new_amount > 0, EscrowError::ZeroAmount);
+    // Timeouts can only be extended here, and stay within the cap set at init
+    require!(
+        timeout_seconds >= old_timeout_seconds,
+        EscrowError::TimeoutNotExtended
+    );
+    require!(timeout_seconds <= 30 * 24 * 3600, EscrowError::TimeoutTooLong); // Max 30 days
+
+    // A top-up is held to the mint's policy as of now: KYC on the escrow's new size, and the
+    // increase counts against both parties' rolling volume and the mint's TVL cap
+    let policy = MintPolicy::load(&ctx.accounts.mint_policy)?;
+    if new_amount > old_amount {
+        // A pause stops new funds coming in, but reductions and timeout changes still go through
+        require!(!ctx.accounts.config.paused, EscrowError::ProgramPaused);
+
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(policy) = &policy {
+            let parties = [
+                (
+                    ctx.accounts.depositor_kyc.as_deref(),
+                    ctx.accounts.depositor_kyc_attestor.as_deref(),
+                ),
+                (
+                    ctx.accounts.beneficiary_kyc.as_deref(),
+                    ctx.accounts.beneficiary_kyc_attestor.as_deref(),
+                ),
+            ];
+            policy.check_kyc(new_amount, parties, now)?;
+        }
+
+        let parties = [
+            PartyVolume {
+                wallet: escrow.depositor,
+                tracker: ctx.accounts.depositor_volume.as_deref_mut(),
+                bump: ctx.bumps.depositor_volume,
+                kyc: ctx.accounts.depositor_kyc.as_deref(),
+                attestor: ctx.accounts.depositor_kyc_attestor.as_deref(),
+            },
+            PartyVolume {
+                wallet: escrow.beneficiary,
+                tracker: ctx.accounts.beneficiary_volume.as_deref_mut(),
+                bump: ctx.bumps.beneficiary_volume,
+                kyc: ctx.accounts.beneficiary_kyc.as_deref(),
+                attestor: ctx.accounts.beneficiary_kyc_attestor.as_deref(),
+            },
+        ];
+        record_volume(
+            policy.as_ref(),
+            ctx.accounts.mint.key(),
+            parties,
+            new_amount - old_amount,
+            now,
+        )?;
+    }
+
+    let decimals = ctx.accounts.mint.decimals;
+    if new_amount > old_amount {
+        // Top-up: depositor moves the difference into the vault
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.depositor_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_ctx, new_amount - old_amount, decimals)?;
+    } else if new_amount < old_amount {
+        // Reduction: the vault returns the difference to the depositor
+        let destination = escrow.refund_destination(
+            Some(&ctx.accounts.depositor_token_account),
+            ctx.accounts.refund_token_account.as_ref(),
+        )?;
+
+        let trade_id = escrow.trade_id;
+        let depositor_key = escrow.depositor;
+        let bump = escrow.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            trade_id.as_ref(),
+            depositor_key.as_ref(),
+            &[bump],
+        ]];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            to: destination.to_account_info(),
+            authority: ctx.accounts.escrow_state.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        transfer_checked(cpi_ctx, old_amount - new_amount, decimals)?;
+    }
+
+    let stats = &mut ctx.accounts.global_stats;
+    if new_amount > old_amount {
+        let tvl_cap = policy.as_ref().and_then(|policy| policy.tvl_cap);
+        stats.deposited(new_amount - old_amount, tvl_cap)?;
+    } else {
+        stats.returned(old_amount - new_amount);
+    }
+
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.amount = new_amount;
+    escrow.timeout_seconds = timeout_seconds;
+
+    emit!(EscrowAmended {
+        trade_id: escrow.trade_id,
+        old_amount,
+        new_amount,
+        old_timeout_seconds,
+        new_timeout_seconds: timeout_seconds,
+    });
+
+    msg!("Escrow amended: {} -> {} tokens", old_amount, new_amount);
+    Ok(())
+}
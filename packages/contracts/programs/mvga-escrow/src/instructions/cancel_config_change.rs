@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, ConfigChangeCancelled, PendingConfigChange, Role};
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    /// Current config admin — receives the pending change's rent
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = config_admin,
+        seeds = [b"config_change", pending_change.id.to_le_bytes().as_ref()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+}
+
+pub fn handle_cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+    let pending = &ctx.accounts.pending_change;
+
+    emit!(ConfigChangeCancelled {
+        id: pending.id,
+        change: pending.change,
+        cancelled_by: ctx.accounts.config_admin.key(),
+    });
+
+    msg!("Config change {} cancelled", pending.id);
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{OrderNonce, SignedOrderCancelled};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CancelSignedOrder<'info> {
+    /// Seller revoking an order they signed
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// Consumes the order nonce — fails if the order was already filled or cancelled
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + OrderNonce::INIT_SPACE,
+        seeds = [b"order_nonce", seller.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub order_nonce: Account<'info, OrderNonce>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_cancel_signed_order(ctx: Context<CancelSignedOrder>, nonce: u64) -> Result<()> {
+    let order_nonce = &mut ctx.accounts.order_nonce;
+    order_nonce.seller = ctx.accounts.seller.key();
+    order_nonce.nonce = nonce;
+    order_nonce.cancelled = true;
+    order_nonce.bump = ctx.bumps.order_nonce;
+
+    emit!(SignedOrderCancelled {
+        seller: order_nonce.seller,
+        nonce,
+    });
+
+    msg!("Signed order {} cancelled", nonce);
+    Ok(())
+}
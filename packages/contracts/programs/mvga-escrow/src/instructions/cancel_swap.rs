@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+    CloseAccount, close_account,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{GlobalStats, SwapCancelled, SwapState};
+
+#[derive(Accounts)]
+pub struct CancelSwap<'info> {
+    /// Maker withdrawing an untaken swap
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        constraint = mint_a.key() == swap_state.mint_a @ EscrowError::InvalidMint,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = maker,
+        constraint = swap_state.maker == maker.key() @ EscrowError::UnauthorizedMaker,
+        seeds = [b"swap", swap_state.swap_id.as_ref(), swap_state.maker.as_ref()],
+        bump = swap_state.bump,
+    )]
+    pub swap_state: Account<'info, SwapState>,
+
+    /// Vault holding the maker's token A
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = swap_state,
+        token::token_program = token_program_a,
+        seeds = [b"vault", swap_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Maker's token A account to receive the refund
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = maker,
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// Per-mint statistics — created if missing, since older swaps weren't counted in them
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint_a.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_cancel_swap(ctx: Context<CancelSwap>) -> Result<()> {
+    let swap = &ctx.accounts.swap_state;
+    let amount_a = swap.amount_a;
+
+    let swap_id = swap.swap_id;
+    let maker_key = swap.maker;
+    let bump = swap.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"swap",
+        swap_id.as_ref(),
+        maker_key.as_ref(),
+        &[bump],
+    ]];
+
+    // Transfer token A from vault back to maker
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.maker_token_account_a.to_account_info(),
+        authority: ctx.accounts.swap_state.to_account_info(),
+        mint: ctx.accounts.mint_a.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program_a.to_account_info(), cpi_accounts)
+        .with_signer(signer_seeds);
+    transfer_checked(cpi_ctx, amount_a, ctx.accounts.mint_a.decimals)?;
+
+    // Close vault account
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.maker.to_account_info(),
+        authority: ctx.accounts.swap_state.to_account_info(),
+    };
+    let close_ctx = CpiContext::new(ctx.accounts.token_program_a.to_account_info(), close_accounts)
+        .with_signer(signer_seeds);
+    close_account(close_ctx)?;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint_a.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.returned(amount_a);
+
+    emit!(SwapCancelled {
+        swap_id,
+        maker: maker_key,
+        amount_a,
+    });
+
+    msg!("Swap cancelled: {} tokens returned to maker", amount_a);
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+    CloseAccount, close_account,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{
+    Config, EscrowState, EscrowStatus, GlobalStats, ReferrerStats, VestedClaimed, VestingCancelled,
+};
+
+use super::release::{pay_beneficiary, FeeAccounts, ReferrerAccounts};
+
+#[derive(Accounts)]
+pub struct CancelVesting<'info> {
+    /// Depositor stopping the stream
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// CHECK: Whoever paid the escrow's rent gets it back when the escrow closes
+    #[account(
+        mut,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary receives whatever has vested but not yet been claimed
+    #[account(
+        constraint = beneficiary.key() == escrow_state.beneficiary @ EscrowError::UnauthorizedBeneficiary,
+    )]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — holds the platform fee settings
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        constraint = escrow_state.depositor == depositor.key() @ EscrowError::UnauthorizedDepositor,
+        constraint = escrow_state.status == EscrowStatus::Locked @ EscrowError::InvalidStatus,
+        constraint = escrow_state.mode.is_vesting() @ EscrowError::InvalidMode,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Vault holding the unclaimed tokens
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Beneficiary's associated token account for the vested remainder — omit when they chose a payout account
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Payout account the beneficiary chose at acceptance or `mark_paid`
+    #[account(mut)]
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's token account to receive the unvested remainder — omit when they chose
+    /// a refund account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = depositor,
+    )]
+    pub depositor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Refund account the depositor chose at init or through `set_refund_account`
+    #[account(mut)]
+    pub refund_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    /// Fee recipient's token account — required when the vested payout carries a platform fee
+    #[account(mut)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", depositor_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = depositor_referrer.bump,
+    )]
+    pub depositor_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the depositor's referrer is paid its fee share into
+    #[account(mut)]
+    pub depositor_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Beneficiary's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", beneficiary_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = beneficiary_referrer.bump,
+    )]
+    pub beneficiary_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the beneficiary's referrer is paid its fee share into
+    #[account(mut)]
+    pub beneficiary_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+pub fn handle_cancel_vesting(ctx: Context<CancelVesting>) -> Result<()> {
+    let escrow = &ctx.accounts.escrow_state;
+    let decimals = ctx.accounts.mint.decimals;
+    let clock = Clock::get()?;
+
+    // Split the vault: vested-but-unclaimed to the beneficiary, the rest back to the depositor
+    let vested = escrow.vested_amount(clock.unix_timestamp)?;
+    let claimed = escrow.vested_claimed()?;
+    let vested_unclaimed = vested.saturating_sub(claimed);
+    let refunded = escrow.amount - vested_unclaimed;
+
+    let payout_destination = escrow.payout_destination(
+        ctx.accounts.beneficiary_token_account.as_ref(),
+        ctx.accounts.payout_token_account.as_ref(),
+    )?;
+    let refund_destination = escrow.refund_destination(
+        ctx.accounts.depositor_token_account.as_ref(),
+        ctx.accounts.refund_token_account.as_ref(),
+    )?;
+
+    let trade_id = escrow.trade_id;
+    let depositor_key = escrow.depositor;
+    let bump = escrow.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"escrow",
+        trade_id.as_ref(),
+        depositor_key.as_ref(),
+        &[bump],
+    ]];
+
+    if vested_unclaimed > 0 {
+        // Same fee and referral split as a claim by the beneficiary
+        pay_beneficiary(
+            &ctx.accounts.escrow_state,
+            &mut ctx.accounts.global_stats,
+            FeeAccounts {
+                config: &ctx.accounts.config,
+                fee_token_account: ctx.accounts.fee_token_account.as_ref(),
+                referrers: [
+                    ReferrerAccounts {
+                        stats: ctx.accounts.depositor_referrer.as_mut(),
+                        token_account: ctx.accounts.depositor_referrer_token_account.as_ref(),
+                    },
+                    ReferrerAccounts {
+                        stats: ctx.accounts.beneficiary_referrer.as_mut(),
+                        token_account: ctx.accounts.beneficiary_referrer_token_account.as_ref(),
+                    },
+                ],
+            },
+            &ctx.accounts.vault,
+            payout_destination,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            vested_unclaimed,
+        )?;
+
+        emit!(VestedClaimed {
+            trade_id,
+            beneficiary: ctx.accounts.beneficiary.key(),
+            amount: vested_unclaimed,
+            total_claimed: vested,
+            remaining: refunded,
+        });
+    }
+
+    if refunded > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            to: refund_destination.to_account_info(),
+            authority: ctx.accounts.escrow_state.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        transfer_checked(cpi_ctx, refunded, decimals)?;
+    }
+
+    // Close vault account, return rent to the rent payer
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.rent_payer.to_account_info(),
+        authority: ctx.accounts.escrow_state.to_account_info(),
+    };
+    let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
+        .with_signer(signer_seeds);
+    close_account(close_ctx)?;
+
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.amount = 0;
+    escrow.status = EscrowStatus::Refunded;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.returned(refunded);
+    stats.moved(EscrowStatus::Locked, EscrowStatus::Refunded);
+
+    emit!(VestingCancelled {
+        trade_id,
+        depositor: depositor_key,
+        vested_paid: vested_unclaimed,
+        refunded,
+    });
+
+    msg!("Vesting cancelled: {} tokens returned to depositor", refunded);
+    Ok(())
+}
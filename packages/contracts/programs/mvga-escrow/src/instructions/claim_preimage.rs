@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface,
+    CloseAccount, close_account,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{
+    Config, EscrowMode, EscrowReleased, EscrowState, EscrowStatus, GlobalStats, HashlockClaimed,
+    ReferrerStats, MAX_PREIMAGE_LEN,
+};
+
+use super::release::{pay_beneficiary, FeeAccounts, ReferrerAccounts};
+
+#[derive(Accounts)]
+pub struct ClaimWithPreimage<'info> {
+    /// Anyone holding the preimage — pays for the beneficiary's token account if needed
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+
+    /// CHECK: Beneficiary receives the escrowed tokens
+    #[account(
+        constraint = beneficiary.key() == escrow_state.beneficiary @ EscrowError::UnauthorizedBeneficiary,
+    )]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    /// CHECK: Rent payer receives rent from the closed vault and escrow state
+    #[account(
+        mut,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — holds the platform fee settings
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        constraint = escrow_state.status == EscrowStatus::Locked @ EscrowError::InvalidStatus,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Vault holding the escrowed tokens
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Beneficiary's associated token account — omit when they chose a payout account
+    #[account(
+        init_if_needed,
+        payer = submitter,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Payout account the beneficiary chose at acceptance or `mark_paid`
+    #[account(mut)]
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    /// Fee recipient's token account — required when the payout carries a platform fee
+    #[account(mut)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", depositor_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = depositor_referrer.bump,
+    )]
+    pub depositor_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the depositor's referrer is paid its fee share into
+    #[account(mut)]
+    pub depositor_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Beneficiary's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", beneficiary_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = beneficiary_referrer.bump,
+    )]
+    pub beneficiary_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the beneficiary's referrer is paid its fee share into
+    #[account(mut)]
+    pub beneficiary_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+pub fn handle_claim_with_preimage(ctx: Context<ClaimWithPreimage>, preimage: Vec<u8>) -> Result<()> {
+    let escrow = &ctx.accounts.escrow_state;
+
+    let (algorithm, hash) = match escrow.mode {
+        EscrowMode::Hashlock { algorithm, hash } => (algorithm, hash),
+        _ => return Err(EscrowError::InvalidMode.into()),
+    };
+    require!(
+        !preimage.is_empty() && preimage.len() <= MAX_PREIMAGE_LEN,
+        EscrowError::InvalidPreimage
+    );
+    require!(algorithm.digest(&preimage) == hash, EscrowError::InvalidPreimage);
+    // Past the deadline the escrow belongs to the refund path, so a claim can't race it and
+    // the counter-leg keyed to the same hash always has time left once this one is claimed
+    require!(
+        Clock::get()?.unix_timestamp < escrow.refund_deadline()?,
+        EscrowError::HashlockExpired
+    );
+
+    let amount = escrow.amount;
+
+    let destination = escrow.payout_destination(
+        ctx.accounts.beneficiary_token_account.as_ref(),
+        ctx.accounts.payout_token_account.as_ref(),
+    )?;
+
+    let trade_id = escrow.trade_id;
+    let depositor_key = escrow.depositor;
+    let bump = escrow.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"escrow",
+        trade_id.as_ref(),
+        depositor_key.as_ref(),
+        &[bump],
+    ]];
+
+    // Transfer tokens from vault to beneficiary, less the platform fee
+    pay_beneficiary(
+        &ctx.accounts.escrow_state,
+        &mut ctx.accounts.global_stats,
+        FeeAccounts {
+            config: &ctx.accounts.config,
+            fee_token_account: ctx.accounts.fee_token_account.as_ref(),
+            referrers: [
+                ReferrerAccounts {
+                    stats: ctx.accounts.depositor_referrer.as_mut(),
+                    token_account: ctx.accounts.depositor_referrer_token_account.as_ref(),
+                },
+                ReferrerAccounts {
+                    stats: ctx.accounts.beneficiary_referrer.as_mut(),
+                    token_account: ctx.accounts.beneficiary_referrer_token_account.as_ref(),
+                },
+            ],
+        },
+        &ctx.accounts.vault,
+        destination,
+        &ctx.accounts.mint,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+
+    // Close vault account, return rent to the rent payer
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.rent_payer.to_account_info(),
+        authority: ctx.accounts.escrow_state.to_account_info(),
+    };
+    let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
+        .with_signer(signer_seeds);
+    close_account(close_ctx)?;
+
+    // Update status
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.status = EscrowStatus::Released;
+
+    ctx.accounts.global_stats.moved(EscrowStatus::Locked, EscrowStatus::Released);
+
+    emit!(HashlockClaimed {
+        trade_id,
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount,
+        preimage,
+    });
+
+    emit!(EscrowReleased {
+        trade_id,
+        depositor: depositor_key,
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount,
+        attestor: None,
+    });
+
+    msg!("Hashlock claimed: {} tokens sent to beneficiary", amount);
+    Ok(())
+}
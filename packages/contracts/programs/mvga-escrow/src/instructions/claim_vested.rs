@@ -0,0 +1,208 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface,
+    CloseAccount, close_account,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{
+    Config, EscrowReleased, EscrowState, EscrowStatus, GlobalStats, ReferrerStats, VestedClaimed,
+};
+
+use super::release::{pay_beneficiary, FeeAccounts, ReferrerAccounts};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    /// Beneficiary claiming the unlocked portion
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    /// CHECK: Whoever paid the escrow's rent gets it back once the last tokens are claimed
+    #[account(
+        mut,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — holds the platform fee settings
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Closed to the rent payer once everything has been claimed
+    #[account(
+        mut,
+        constraint = escrow_state.beneficiary == beneficiary.key() @ EscrowError::UnauthorizedBeneficiary,
+        constraint = escrow_state.status == EscrowStatus::Locked @ EscrowError::InvalidStatus,
+        constraint = escrow_state.mode.is_vesting() @ EscrowError::InvalidMode,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Vault holding the unclaimed tokens
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Beneficiary's associated token account — omit when they chose a payout account
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Payout account the beneficiary chose at acceptance or `mark_paid`
+    #[account(mut)]
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    /// Fee recipient's token account — required when the payout carries a platform fee
+    #[account(mut)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", depositor_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = depositor_referrer.bump,
+    )]
+    pub depositor_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the depositor's referrer is paid its fee share into
+    #[account(mut)]
+    pub depositor_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Beneficiary's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", beneficiary_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = beneficiary_referrer.bump,
+    )]
+    pub beneficiary_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the beneficiary's referrer is paid its fee share into
+    #[account(mut)]
+    pub beneficiary_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+pub fn handle_claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+    let escrow = &ctx.accounts.escrow_state;
+    let clock = Clock::get()?;
+
+    let vested = escrow.vested_amount(clock.unix_timestamp)?;
+    let claimed = escrow.vested_claimed()?;
+    let claimable = vested.saturating_sub(claimed);
+    require!(claimable > 0, EscrowError::NothingToClaim);
+
+    let destination = escrow.payout_destination(
+        ctx.accounts.beneficiary_token_account.as_ref(),
+        ctx.accounts.payout_token_account.as_ref(),
+    )?;
+
+    let trade_id = escrow.trade_id;
+    let depositor_key = escrow.depositor;
+    let bump = escrow.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"escrow",
+        trade_id.as_ref(),
+        depositor_key.as_ref(),
+        &[bump],
+    ]];
+
+    // Transfer the unlocked tokens from vault to beneficiary, less the platform fee
+    pay_beneficiary(
+        &ctx.accounts.escrow_state,
+        &mut ctx.accounts.global_stats,
+        FeeAccounts {
+            config: &ctx.accounts.config,
+            fee_token_account: ctx.accounts.fee_token_account.as_ref(),
+            referrers: [
+                ReferrerAccounts {
+                    stats: ctx.accounts.depositor_referrer.as_mut(),
+                    token_account: ctx.accounts.depositor_referrer_token_account.as_ref(),
+                },
+                ReferrerAccounts {
+                    stats: ctx.accounts.beneficiary_referrer.as_mut(),
+                    token_account: ctx.accounts.beneficiary_referrer_token_account.as_ref(),
+                },
+            ],
+        },
+        &ctx.accounts.vault,
+        destination,
+        &ctx.accounts.mint,
+        &ctx.accounts.token_program,
+        claimable,
+    )?;
+
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.amount -= claimable;
+    let remaining = escrow.amount;
+
+    emit!(VestedClaimed {
+        trade_id,
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount: claimable,
+        total_claimed: claimed + claimable,
+        remaining,
+    });
+
+    if remaining > 0 {
+        msg!("Vested claim: {} tokens sent to beneficiary", claimable);
+        return Ok(());
+    }
+
+    // Fully vested and claimed — close vault account, return rent to the rent payer
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.rent_payer.to_account_info(),
+        authority: ctx.accounts.escrow_state.to_account_info(),
+    };
+    let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
+        .with_signer(signer_seeds);
+    close_account(close_ctx)?;
+
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.status = EscrowStatus::Released;
+    ctx.accounts.global_stats.moved(EscrowStatus::Locked, EscrowStatus::Released);
+    escrow.close(ctx.accounts.rent_payer.to_account_info())?;
+
+    emit!(EscrowReleased {
+        trade_id,
+        depositor: depositor_key,
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount: claimable,
+        attestor: None,
+    });
+
+    msg!("Vesting complete: {} tokens sent to beneficiary", claimable);
+    Ok(())
+}
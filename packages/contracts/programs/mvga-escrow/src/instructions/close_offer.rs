@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+    CloseAccount, close_account,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{GlobalStats, Offer, OfferClosed};
+
+#[derive(Accounts)]
+pub struct CloseOffer<'info> {
+    /// Seller taking the offer down — child escrows already filled are unaffected
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        constraint = mint.key() == offer.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = seller,
+        constraint = offer.seller == seller.key() @ EscrowError::UnauthorizedDepositor,
+        seeds = [b"offer", offer.offer_id.as_ref(), offer.seller.as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Offer vault holding the unreserved liquidity
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = offer,
+        token::token_program = token_program,
+        seeds = [b"vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub offer_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Seller's token account to receive the remaining liquidity
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Per-mint statistics — created if missing, since older offers weren't counted in them
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_close_offer(ctx: Context<CloseOffer>) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    let amount = offer.available;
+
+    let offer_id = offer.offer_id;
+    let seller_key = offer.seller;
+    let bump = offer.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"offer",
+        offer_id.as_ref(),
+        seller_key.as_ref(),
+        &[bump],
+    ]];
+
+    // Return remaining liquidity to seller
+    if amount > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.offer_vault.to_account_info(),
+            to: ctx.accounts.seller_token_account.to_account_info(),
+            authority: ctx.accounts.offer.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+    }
+
+    // Close offer vault, return rent to seller
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.offer_vault.to_account_info(),
+        destination: ctx.accounts.seller.to_account_info(),
+        authority: ctx.accounts.offer.to_account_info(),
+    };
+    let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
+        .with_signer(signer_seeds);
+    close_account(close_ctx)?;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.returned(amount);
+
+    emit!(OfferClosed {
+        offer_id,
+        seller: seller_key,
+        withdrawn: amount,
+    });
+
+    msg!("Offer closed: {} tokens returned to seller", amount);
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, RentSponsor, RentSponsorConfigured, Role};
+
+#[derive(Accounts)]
+pub struct ConfigureRentSponsor<'info> {
+    /// Fee manager
+    #[account(mut)]
+    pub fee_manager: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::FeeManager, &fee_manager.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = fee_manager,
+        space = 8 + RentSponsor::INIT_SPACE,
+        seeds = [b"rent_sponsor"],
+        bump,
+    )]
+    pub rent_sponsor: Account<'info, RentSponsor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_configure_rent_sponsor(
+    ctx: Context<ConfigureRentSponsor>,
+    operator: Pubkey,
+) -> Result<()> {
+    // The sponsor operator spends the pool. Once a config
+    // delay is set, it has to be lowered through the queue before this can run
+    require!(ctx.accounts.config.config_delay == 0, EscrowError::TimelockRequired);
+
+    let sponsor = &mut ctx.accounts.rent_sponsor;
+    sponsor.operator = operator;
+    sponsor.bump = ctx.bumps.rent_sponsor;
+
+    emit!(RentSponsorConfigured { operator });
+
+    msg!("Rent sponsor operator set to {}", operator);
+    Ok(())
+}
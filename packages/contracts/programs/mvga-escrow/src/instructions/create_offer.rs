@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{Config, GlobalStats, MintPolicy, Offer, OfferCreated};
+use crate::AUTHORIZED_ADMIN;
+
+#[derive(Accounts)]
+#[instruction(offer_id: [u8; 16])]
+pub struct CreateOffer<'info> {
+    /// Seller who funds the offer's liquidity pool
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// Admin authority for dispute resolution on child escrows — must match AUTHORIZED_ADMIN
+    /// CHECK: Validated against hardcoded AUTHORIZED_ADMIN constant in handler.
+    pub admin: UncheckedAccount<'info>,
+
+    /// Token mint being offered
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — no new offers while paused
+    #[account(
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Offer PDA
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Offer::INIT_SPACE,
+        seeds = [b"offer", offer_id.as_ref(), seller.key().as_ref()],
+        bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Vault token account owned by the offer PDA
+    #[account(
+        init,
+        payer = seller,
+        token::mint = mint,
+        token::authority = offer,
+        token::token_program = token_program,
+        seeds = [b"vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub offer_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Seller's token account to transfer from
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Seller's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", seller.key().as_ref()],
+        bump,
+        constraint = seller_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub seller_denylist: UncheckedAccount<'info>,
+
+    /// Per-mint statistics — the offer's liquidity counts towards the TVL cap
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// CHECK: The mint's policy PDA — may not exist, in which case the mint is uncapped
+    #[account(
+        seeds = [b"mint_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_create_offer(
+    ctx: Context<CreateOffer>,
+    offer_id: [u8; 16],
+    amount: u64,
+    min_fill: u64,
+    max_fill: u64,
+    payment_methods: u32,
+    price: u64,
+    fiat_currency: [u8; 3],
+    timeout_seconds: u64,
+) -> Result<()> {
+    require!(amount > 0, EscrowError::ZeroAmount);
+    require!(
+        min_fill > 0 && min_fill <= max_fill,
+        EscrowError::InvalidFillLimits
+    );
+    require!(payment_methods != 0, EscrowError::PaymentMethodNotAllowed);
+    require!(timeout_seconds > 0, EscrowError::ZeroTimeout);
+    require!(timeout_seconds <= 30 * 24 * 3600, EscrowError::TimeoutTooLong); // Max 30 days
+    require!(
+        ctx.accounts.admin.key() == AUTHORIZED_ADMIN,
+        EscrowError::InvalidAdmin
+    );
+    // Reject mints with freeze authority — prevents funds from being frozen in vault
+    require!(
+        ctx.accounts.mint.freeze_authority.is_none(),
+        EscrowError::MintHasFreezeAuthority
+    );
+
+    let clock = Clock::get()?;
+
+    // Initialize offer
+    let offer = &mut ctx.accounts.offer;
+    offer.offer_id = offer_id;
+    offer.seller = ctx.accounts.seller.key();
+    offer.mint = ctx.accounts.mint.key();
+    offer.available = amount;
+    offer.min_fill = min_fill;
+    offer.max_fill = max_fill;
+    offer.payment_methods = payment_methods;
+    offer.price = price;
+    offer.fiat_currency = fiat_currency;
+    offer.timeout_seconds = timeout_seconds;
+    offer.admin = ctx.accounts.admin.key();
+    offer.fill_count = 0;
+    offer.created_at = clock.unix_timestamp;
+    offer.bump = ctx.bumps.offer;
+
+    // Transfer liquidity from seller to offer vault
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.seller_token_account.to_account_info(),
+        to: ctx.accounts.offer_vault.to_account_info(),
+        authority: ctx.accounts.seller.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+    // Offer liquidity sits in a program vault like escrowed funds, so it is capped the same way
+    let policy = MintPolicy::load(&ctx.accounts.mint_policy)?;
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.deposited(amount, policy.and_then(|policy| policy.tvl_cap))?;
+
+    emit!(OfferCreated {
+        offer_id,
+        seller: ctx.accounts.seller.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+        min_fill,
+        max_fill,
+        payment_methods,
+        price,
+        fiat_currency,
+    });
+
+    msg!("Offer created: {} tokens available", amount);
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{
+    SessionKey, SessionKeyCreated, MAX_SESSION_DURATION, SESSION_FILE_DISPUTE, SESSION_MARK_PAID,
+    SESSION_RELEASE,
+};
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct CreateSessionKey<'info> {
+    /// Wallet delegating actions to the session key
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + SessionKey::INIT_SPACE,
+        seeds = [b"session", owner.key().as_ref(), delegate.as_ref()],
+        bump,
+    )]
+    pub session_key: Account<'info, SessionKey>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_create_session_key(
+    ctx: Context<CreateSessionKey>,
+    delegate: Pubkey,
+    permissions: u8,
+    expires_at: i64,
+    spend_cap: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let all = SESSION_MARK_PAID | SESSION_RELEASE | SESSION_FILE_DISPUTE;
+
+    require!(
+        permissions != 0 && permissions & !all == 0,
+        EscrowError::InvalidSessionKey
+    );
+    require!(
+        delegate != ctx.accounts.owner.key(),
+        EscrowError::InvalidSessionKey
+    );
+    require!(
+        expires_at > now && expires_at - now <= MAX_SESSION_DURATION,
+        EscrowError::InvalidSessionExpiry
+    );
+
+    let session = &mut ctx.accounts.session_key;
+    session.owner = ctx.accounts.owner.key();
+    session.delegate = delegate;
+    session.permissions = permissions;
+    session.expires_at = expires_at;
+    session.spend_cap = spend_cap;
+    session.spent = 0;
+    session.bump = ctx.bumps.session_key;
+
+    emit!(SessionKeyCreated {
+        owner: session.owner,
+        delegate,
+        permissions,
+        expires_at,
+        spend_cap,
+    });
+
+    msg!("Session key created for delegate {}", delegate);
+    Ok(())
+}
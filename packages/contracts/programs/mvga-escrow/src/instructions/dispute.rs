@@ -1,30 +1,61 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::EscrowError;
-use crate::state::{DisputeFiled, EscrowState, EscrowStatus};
+use crate::state::{
+    DisputeFiled, EscrowState, EscrowStatus, GlobalStats, SessionKey, SESSION_FILE_DISPUTE,
+};
 
 #[derive(Accounts)]
 pub struct FileDispute<'info> {
-    /// Either buyer or seller can file a dispute
+    /// Either depositor or beneficiary can file a dispute, or a session delegate acting for them
     pub disputer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.seller.as_ref()],
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
         bump = escrow_state.bump,
     )]
     pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Disputing party's session key — omit when the party signs directly
+    #[account(
+        seeds = [b"session", session_key.owner.as_ref(), session_key.delegate.as_ref()],
+        bump = session_key.bump,
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
 }
 
 pub fn handle_dispute(ctx: Context<FileDispute>) -> Result<()> {
     let escrow = &mut ctx.accounts.escrow_state;
-    let disputer = ctx.accounts.disputer.key();
+    let session = ctx.accounts.session_key.as_deref();
 
-    // Only buyer or seller can dispute
+    // Only depositor or beneficiary can dispute — directly or through their session key
+    let disputer = match session {
+        Some(session) if ctx.accounts.disputer.key() == session.delegate => session.owner,
+        _ => ctx.accounts.disputer.key(),
+    };
     require!(
-        disputer == escrow.buyer || disputer == escrow.seller,
-        EscrowError::UnauthorizedBuyer
+        disputer == escrow.beneficiary || disputer == escrow.depositor,
+        EscrowError::UnauthorizedBeneficiary
     );
+    SessionKey::authorize(
+        session,
+        &ctx.accounts.disputer.key(),
+        &disputer,
+        SESSION_FILE_DISPUTE,
+        EscrowError::UnauthorizedBeneficiary,
+    )?;
+
+    // Vesting and hashlock escrows have no off-chain leg to dispute
+    require!(escrow.mode.has_offchain_leg(), EscrowError::InvalidMode);
 
     // Can only dispute from Locked or PaymentSent
     require!(
@@ -32,6 +63,7 @@ pub fn handle_dispute(ctx: Context<FileDispute>) -> Result<()> {
         EscrowError::InvalidStatus
     );
 
+    ctx.accounts.global_stats.moved(escrow.status, EscrowStatus::Disputed);
     escrow.status = EscrowStatus::Disputed;
 
     emit!(DisputeFiled {
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{
+    Config, Denylisted, DenylistedEscrowDisputed, EscrowState, EscrowStatus, GlobalStats, Role,
+};
+
+#[derive(Accounts)]
+pub struct DisputeDenylistedEscrow<'info> {
+    /// Config admin
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Denylist entry of one of the escrow's parties
+    #[account(
+        seeds = [b"denylist", denylisted.wallet.as_ref()],
+        bump = denylisted.bump,
+    )]
+    pub denylisted: Account<'info, Denylisted>,
+
+    #[account(
+        mut,
+        constraint = escrow_state.depositor == denylisted.wallet
+            || escrow_state.beneficiary == denylisted.wallet @ EscrowError::WalletNotInvolved,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+}
+
+pub fn handle_dispute_denylisted_escrow(ctx: Context<DisputeDenylistedEscrow>) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow_state;
+
+    // Funds stay in the vault for the arbiter to settle rather than being frozen outright
+    require!(
+        escrow.status == EscrowStatus::Locked || escrow.status == EscrowStatus::PaymentSent,
+        EscrowError::InvalidStatus
+    );
+    ctx.accounts.global_stats.moved(escrow.status, EscrowStatus::Disputed);
+    escrow.status = EscrowStatus::Disputed;
+
+    let wallet = ctx.accounts.denylisted.wallet;
+    emit!(DenylistedEscrowDisputed {
+        trade_id: escrow.trade_id,
+        wallet,
+        config_admin: ctx.accounts.config_admin.key(),
+    });
+
+    msg!("Escrow routed to dispute: {} is denylisted", wallet);
+    Ok(())
+}
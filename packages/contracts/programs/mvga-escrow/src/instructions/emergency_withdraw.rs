@@ -0,0 +1,207 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+    CloseAccount, close_account,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{Config, EmergencyWithdrawn, EscrowState, EscrowStatus, GlobalStats};
+
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    /// Anyone — usually one of the parties — pays for missing token accounts
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// Program config — the exit only opens after a pause outlasts the grace period
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Whoever paid the escrow's rent gets it back when the escrow closes
+    #[account(
+        mut,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Depositor receives the locked funds, or half of them once payment was claimed
+    #[account(
+        constraint = depositor.key() == escrow_state.depositor @ EscrowError::UnauthorizedDepositor,
+    )]
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary receives half of the funds once payment was claimed
+    #[account(
+        constraint = beneficiary.key() == escrow_state.beneficiary @ EscrowError::UnauthorizedBeneficiary,
+    )]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Vault holding escrowed tokens
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Depositor's associated token account — omit when they chose a refund account
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Refund account the depositor chose at init or through `set_refund_account`
+    #[account(mut)]
+    pub refund_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Beneficiary's associated token account — only needed for a split, and omitted when
+    /// they chose a payout account
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Payout account the beneficiary chose at acceptance or `mark_paid`
+    #[account(mut)]
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(
+        ctx.accounts.config.emergency_exit_open(clock.unix_timestamp),
+        EscrowError::EmergencyExitClosed
+    );
+
+    let escrow = &ctx.accounts.escrow_state;
+    let amount = escrow.amount;
+    let status = escrow.status;
+    let decimals = ctx.accounts.mint.decimals;
+
+    // Nobody claimed payment yet: everything goes back to the depositor. Once payment was
+    // claimed there is no one left to judge it, so the funds are split down the middle with
+    // the odd unit going to the depositor.
+    let to_beneficiary = match status {
+        EscrowStatus::Locked => 0,
+        EscrowStatus::PaymentSent | EscrowStatus::Disputed => amount / 2,
+        _ => return Err(EscrowError::InvalidStatus.into()),
+    };
+    let to_depositor = amount - to_beneficiary;
+
+    let trade_id = escrow.trade_id;
+    let depositor_key = escrow.depositor;
+    let bump = escrow.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"escrow",
+        trade_id.as_ref(),
+        depositor_key.as_ref(),
+        &[bump],
+    ]];
+
+    if to_depositor > 0 {
+        let destination = escrow.refund_destination(
+            ctx.accounts.depositor_token_account.as_ref(),
+            ctx.accounts.refund_token_account.as_ref(),
+        )?;
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            to: destination.to_account_info(),
+            authority: ctx.accounts.escrow_state.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        transfer_checked(cpi_ctx, to_depositor, decimals)?;
+    }
+
+    if to_beneficiary > 0 {
+        let destination = escrow.payout_destination(
+            ctx.accounts.beneficiary_token_account.as_ref(),
+            ctx.accounts.payout_token_account.as_ref(),
+        )?;
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            to: destination.to_account_info(),
+            authority: ctx.accounts.escrow_state.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        transfer_checked(cpi_ctx, to_beneficiary, decimals)?;
+    }
+
+    // Close vault account, return rent to the rent payer
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.rent_payer.to_account_info(),
+        authority: ctx.accounts.escrow_state.to_account_info(),
+    };
+    let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
+        .with_signer(signer_seeds);
+    close_account(close_ctx)?;
+
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.amount = 0;
+    escrow.status = EscrowStatus::Refunded;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.paid_out(to_beneficiary);
+    stats.returned(to_depositor);
+    stats.moved(status, EscrowStatus::Refunded);
+
+    emit!(EmergencyWithdrawn {
+        trade_id,
+        depositor: depositor_key,
+        beneficiary: ctx.accounts.beneficiary.key(),
+        status,
+        to_depositor,
+        to_beneficiary,
+    });
+
+    msg!(
+        "Emergency withdrawal: {} tokens to depositor, {} to beneficiary",
+        to_depositor,
+        to_beneficiary
+    );
+    Ok(())
+}
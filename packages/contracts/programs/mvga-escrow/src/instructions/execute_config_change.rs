@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, ConfigChangeExecuted, PendingConfigChange, Role};
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    /// Current config admin — receives the pending change's rent
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = config_admin,
+        seeds = [b"config_change", pending_change.id.to_le_bytes().as_ref()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+}
+
+pub fn handle_execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+    let pending = &ctx.accounts.pending_change;
+    require!(
+        Clock::get()?.unix_timestamp >= pending.executable_at,
+        EscrowError::TimelockNotElapsed
+    );
+
+    let id = pending.id;
+    let change = pending.change;
+    ctx.accounts.config.apply(&change)?;
+
+    emit!(ConfigChangeExecuted {
+        id,
+        change,
+        executor: ctx.accounts.config_admin.key(),
+    });
+
+    msg!("Config change {} executed", id);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{EscrowMode, EscrowState, EscrowStatus, TimeoutExtended};
+
+#[derive(Accounts)]
+pub struct ExtendTimeout<'info> {
+    /// Depositor agreeing to wait longer before self-refunding
+    pub depositor: Signer<'info>,
+
+    /// Beneficiary asking for more time to complete the off-chain leg
+    pub beneficiary: Signer<'info>,
+
+    /// Vesting escrows stream on their own schedule and a hashlock's timeout bounds the
+    /// preimage window both sides priced in, so only standard and milestone escrows qualify
+    #[account(
+        mut,
+        constraint = matches!(escrow_state.mode, EscrowMode::Standard | EscrowMode::Milestones) @ EscrowError::InvalidMode,
+        constraint = escrow_state.depositor == depositor.key() @ EscrowError::UnauthorizedDepositor,
+        constraint = escrow_state.beneficiary == beneficiary.key() @ EscrowError::UnauthorizedBeneficiary,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+}
+
+pub fn handle_extend_timeout(ctx: Context<ExtendTimeout>, new_timeout_seconds: u64) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow_state;
+
+    require!(
+        escrow.status == EscrowStatus::Locked || escrow.status == EscrowStatus::PaymentSent,
+        EscrowError::InvalidStatus
+    );
+    let old_timeout_seconds = escrow.timeout_seconds;
+    require!(
+        new_timeout_seconds > old_timeout_seconds,
+        EscrowError::TimeoutNotExtended
+    );
+    // Milestone releases restart locked_at, so the ceiling counts from when the escrow was
+    // opened: the refund deadline never moves past 30 days after that
+    let deadline = i64::try_from(new_timeout_seconds)
+        .ok()
+        .and_then(|timeout| escrow.locked_at.checked_add(timeout))
+        .ok_or(EscrowError::TimeoutOverflow)?;
+    require!(
+        deadline <= escrow.opened_at() + 30 * 24 * 3600,
+        EscrowError::TimeoutTooLong
+    );
+
+    escrow.timeout_seconds = new_timeout_seconds;
+
+    emit!(TimeoutExtended {
+        trade_id: escrow.trade_id,
+        old_timeout_seconds,
+        new_timeout_seconds,
+        refund_deadline: escrow.refund_deadline()?,
+    });
+
+    msg!("Escrow timeout extended to {} seconds", new_timeout_seconds);
+    Ok(())
+}
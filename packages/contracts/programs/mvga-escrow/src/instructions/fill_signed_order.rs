@@ -0,0 +1,356 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+};
+
+use crate::errors::EscrowError;
+use crate::signature::verify_ed25519_signature;
+use crate::state::{
+    Config, EscrowDirection, EscrowInitialized, EscrowMode, EscrowState, EscrowStatus, GlobalStats,
+    KycAttestation, KycAttestor, MintPolicy, OrderNonce, PayoutAccountSet, ReferrerStats,
+    SignedOrder, SignedOrderFilled, VolumeTracker, ESCROW_VERSION,
+};
+use crate::AUTHORIZED_ADMIN;
+
+use super::initialize::{record_volume, PartyVolume};
+
+#[derive(Accounts)]
+#[instruction(order: SignedOrder, trade_id: [u8; 16])]
+pub struct FillSignedOrder<'info> {
+    /// Buyer submitting the seller's signed order — pays rent for the escrow
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Admin authority for dispute resolution — must match AUTHORIZED_ADMIN
+    /// CHECK: Validated against hardcoded AUTHORIZED_ADMIN constant in handler.
+    pub admin: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = mint.key() == order.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — no new escrows while paused
+    #[account(
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Program-wide delegate the seller approves on their token account
+    /// CHECK: PDA with no data, only used as the transfer authority.
+    #[account(
+        seeds = [b"order_authority"],
+        bump,
+    )]
+    pub order_authority: UncheckedAccount<'info>,
+
+    /// Consumes the order nonce — fails if already filled or cancelled
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + OrderNonce::INIT_SPACE,
+        seeds = [b"order_nonce", order.seller.as_ref(), order.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub order_nonce: Account<'info, OrderNonce>,
+
+    /// Escrow state PDA — same seeds as a direct escrow with the seller as depositor
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + EscrowState::INIT_SPACE,
+        seeds = [b"escrow", trade_id.as_ref(), order.seller.as_ref()],
+        bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Vault token account owned by the escrow PDA
+    #[account(
+        init,
+        payer = buyer,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Per-mint statistics, created with the mint's first escrow
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// Seller's token account, with `order_authority` approved as delegate
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = seller_token_account.owner == order.seller @ EscrowError::UnauthorizedDepositor,
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Instructions sysvar, used to find the seller's ed25519 signature.
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: Seller's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", order.seller.as_ref()],
+        bump,
+        constraint = seller_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub seller_denylist: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", buyer.key().as_ref()],
+        bump,
+        constraint = buyer_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub buyer_denylist: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// Token account the buyer wants to be paid into instead of their ATA — omit for the ATA
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Registered referrer of the buyer — omit when they have none
+    #[account(
+        mut,
+        seeds = [b"referrer", buyer_referrer.referrer.as_ref(), mint.key().as_ref()],
+        bump = buyer_referrer.bump,
+    )]
+    pub buyer_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// CHECK: The mint's policy PDA — may not exist, in which case the mint is ungated
+    #[account(
+        seeds = [b"mint_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    /// Seller's KYC attestation — required when the fill is above the mint's threshold
+    #[account(
+        seeds = [b"kyc", order.seller.as_ref()],
+        bump = seller_kyc.bump,
+    )]
+    pub seller_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `seller_kyc`
+    #[account(
+        seeds = [b"kyc_attestor", seller_kyc_attestor.authority.as_ref()],
+        bump = seller_kyc_attestor.bump,
+    )]
+    pub seller_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Buyer's KYC attestation — required when the fill is above the mint's threshold
+    #[account(
+        seeds = [b"kyc", buyer.key().as_ref()],
+        bump = buyer_kyc.bump,
+    )]
+    pub buyer_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `buyer_kyc`
+    #[account(
+        seeds = [b"kyc_attestor", buyer_kyc_attestor.authority.as_ref()],
+        bump = buyer_kyc_attestor.bump,
+    )]
+    pub buyer_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Seller's rolling volume in this mint — required while the mint caps their volume,
+    /// omit otherwise
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", order.seller.as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub seller_volume: Option<Box<Account<'info, VolumeTracker>>>,
+
+    /// Buyer's rolling volume in this mint — required while the mint caps their volume,
+    /// omit otherwise
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", buyer.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub buyer_volume: Option<Box<Account<'info, VolumeTracker>>>,
+}
+
+pub fn handle_fill_signed_order(
+    ctx: Context<FillSignedOrder>,
+    order: SignedOrder,
+    trade_id: [u8; 16],
+    amount: u64,
+) -> Result<()> {
+    // The ed25519 instruction right before this one must carry the seller's signature
+    verify_ed25519_signature(
+        &ctx.accounts.instructions.to_account_info(),
+        &order.seller,
+        &order.message()?,
+    )?;
+
+    let clock = Clock::get()?;
+
+    require!(clock.unix_timestamp < order.expires_at, EscrowError::OrderExpired);
+    require!(amount > 0, EscrowError::ZeroAmount);
+    require!(
+        amount >= order.min_amount && amount <= order.max_amount,
+        EscrowError::FillOutOfRange
+    );
+    require!(order.timeout_seconds > 0, EscrowError::ZeroTimeout);
+    require!(
+        order.timeout_seconds <= 30 * 24 * 3600,
+        EscrowError::TimeoutTooLong
+    ); // Max 30 days
+    require!(
+        ctx.accounts.buyer.key() != order.seller,
+        EscrowError::BeneficiaryCannotBeDepositor
+    );
+    require!(
+        ctx.accounts.admin.key() == AUTHORIZED_ADMIN,
+        EscrowError::InvalidAdmin
+    );
+    // Reject mints with freeze authority — prevents funds from being frozen in vault
+    require!(
+        ctx.accounts.mint.freeze_authority.is_none(),
+        EscrowError::MintHasFreezeAuthority
+    );
+
+    // Large fills on a gated mint need both parties verified, as for a direct escrow
+    let policy = MintPolicy::load(&ctx.accounts.mint_policy)?;
+    if let Some(policy) = &policy {
+        let parties = [
+            (ctx.accounts.seller_kyc.as_deref(), ctx.accounts.seller_kyc_attestor.as_deref()),
+            (ctx.accounts.buyer_kyc.as_deref(), ctx.accounts.buyer_kyc_attestor.as_deref()),
+        ];
+        policy.check_kyc(amount, parties, clock.unix_timestamp)?;
+    }
+
+    // The fill counts against both parties' rolling volume, as for a direct escrow
+    let parties = [
+        PartyVolume {
+            wallet: order.seller,
+            tracker: ctx.accounts.seller_volume.as_deref_mut(),
+            bump: ctx.bumps.seller_volume,
+            kyc: ctx.accounts.seller_kyc.as_deref(),
+            attestor: ctx.accounts.seller_kyc_attestor.as_deref(),
+        },
+        PartyVolume {
+            wallet: ctx.accounts.buyer.key(),
+            tracker: ctx.accounts.buyer_volume.as_deref_mut(),
+            bump: ctx.bumps.buyer_volume,
+            kyc: ctx.accounts.buyer_kyc.as_deref(),
+            attestor: ctx.accounts.buyer_kyc_attestor.as_deref(),
+        },
+    ];
+    record_volume(
+        policy.as_ref(),
+        ctx.accounts.mint.key(),
+        parties,
+        amount,
+        clock.unix_timestamp,
+    )?;
+
+    // Burn the nonce so this signature can't settle again
+    let order_nonce = &mut ctx.accounts.order_nonce;
+    order_nonce.seller = order.seller;
+    order_nonce.nonce = order.nonce;
+    order_nonce.cancelled = false;
+    order_nonce.bump = ctx.bumps.order_nonce;
+
+    // Initialize escrow state — identical to a seller-funded standard escrow
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.trade_id = trade_id;
+    escrow.depositor = order.seller;
+    escrow.beneficiary = ctx.accounts.buyer.key();
+    escrow.mint = ctx.accounts.mint.key();
+    escrow.amount = amount;
+    escrow.status = EscrowStatus::Locked;
+    escrow.admin = ctx.accounts.admin.key();
+    escrow.locked_at = clock.unix_timestamp;
+    escrow.created_at = clock.unix_timestamp;
+    escrow.timeout_seconds = order.timeout_seconds;
+    escrow.bump = ctx.bumps.escrow_state;
+    escrow.direction = EscrowDirection::SellerFunded;
+    escrow.mode = EscrowMode::Standard;
+    escrow.milestones = Vec::new();
+    escrow.next_milestone = 0;
+    escrow.attested_release = false;
+    escrow.rent_payer = ctx.accounts.buyer.key();
+    escrow.payout_account = None;
+    escrow.metadata = None;
+    escrow.payment_reference = None;
+    escrow.payment_reference_updated = false;
+    escrow.version = ESCROW_VERSION;
+    // Offers and signed orders carry no seller referral; the buyer names their own referrer
+    escrow.depositor_referrer = None;
+    escrow.beneficiary_referrer = match ctx.accounts.buyer_referrer.as_mut() {
+        Some(referrer) => Some(referrer.refer([escrow.depositor, escrow.beneficiary])?),
+        None => None,
+    };
+    escrow.refund_account = None;
+    if let Some(payout) = ctx.accounts.payout_token_account.as_ref() {
+        escrow.check_destination(payout)?;
+        escrow.payout_account = Some(payout.key());
+
+        emit!(PayoutAccountSet {
+            trade_id,
+            beneficiary: escrow.beneficiary,
+            account: payout.key(),
+        });
+    }
+
+    // Pull the tokens from the seller through the delegate approval
+    let bump = ctx.bumps.order_authority;
+    let signer_seeds: &[&[&[u8]]] = &[&[b"order_authority", &[bump]]];
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.seller_token_account.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.order_authority.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+        .with_signer(signer_seeds);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.opened(amount, policy.as_ref().and_then(|policy| policy.tvl_cap))?;
+
+    emit!(EscrowInitialized {
+        trade_id,
+        depositor: order.seller,
+        beneficiary: ctx.accounts.buyer.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+        timeout_seconds: order.timeout_seconds,
+        direction: EscrowDirection::SellerFunded,
+        milestone_count: 0,
+        rent_payer: ctx.accounts.buyer.key(),
+        metadata: None,
+    });
+
+    emit!(SignedOrderFilled {
+        trade_id,
+        seller: order.seller,
+        buyer: ctx.accounts.buyer.key(),
+        nonce: order.nonce,
+        amount,
+    });
+
+    msg!("Signed order filled: {} tokens locked", amount);
+    Ok(())
+}
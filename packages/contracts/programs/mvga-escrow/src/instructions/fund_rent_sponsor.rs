@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::errors::EscrowError;
+use crate::state::{RentSponsor, RentSponsorFunded};
+
+#[derive(Accounts)]
+pub struct FundRentSponsor<'info> {
+    /// Treasury (or anyone) topping up the pool
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_sponsor"],
+        bump = rent_sponsor.bump,
+    )]
+    pub rent_sponsor: Account<'info, RentSponsor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_fund_rent_sponsor(ctx: Context<FundRentSponsor>, lamports: u64) -> Result<()> {
+    require!(lamports > 0, EscrowError::ZeroAmount);
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.funder.to_account_info(),
+        to: ctx.accounts.rent_sponsor.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+    transfer(cpi_ctx, lamports)?;
+
+    let sponsor = &mut ctx.accounts.rent_sponsor;
+    sponsor.total_funded = sponsor.total_funded.saturating_add(lamports);
+
+    emit!(RentSponsorFunded {
+        funder: ctx.accounts.funder.key(),
+        lamports,
+    });
+
+    msg!("Rent sponsor funded with {} lamports", lamports);
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, Role, RoleGranted};
+
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    /// Current config admin
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handle_grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+    require!(
+        !ctx.accounts.config.requires_timelock(role),
+        EscrowError::TimelockRequired
+    );
+
+    let previous = ctx.accounts.config.set_holder(role, Some(holder));
+
+    emit!(RoleGranted {
+        role,
+        holder,
+        previous,
+        granted_by: ctx.accounts.config_admin.key(),
+    });
+
+    msg!("Role {:?} granted to {}", role, holder);
+    Ok(())
+}
@@ -4,19 +4,29 @@ use anchor_spl::token_interface::{
 };
 
 use crate::errors::EscrowError;
-use crate::state::{EscrowInitialized, EscrowState, EscrowStatus};
+use crate::state::{
+    Config, EscrowDirection, EscrowInitialized, EscrowMode, EscrowState, EscrowStatus,
+    EscrowTerms, GlobalStats, KycAttestation, KycAttestor, MintPolicy, ReferrerStats,
+    RefundAccountSet, RentSponsor, RentSponsored, TradeMetadata, VolumeTracker, ESCROW_VERSION,
+    MAX_MILESTONES,
+};
 use crate::AUTHORIZED_ADMIN;
 
 #[derive(Accounts)]
 #[instruction(trade_id: [u8; 16])]
 pub struct InitializeEscrow<'info> {
-    /// Seller who locks funds into escrow
+    /// Depositor who locks funds into escrow (seller or buyer, see `direction`)
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// Pays rent for the escrow state and vault and gets it back on close — the depositor
+    /// itself, a relayer for users holding only tokens, or the rent sponsor's operator
     #[account(mut)]
-    pub seller: Signer<'info>,
+    pub rent_payer: Signer<'info>,
 
-    /// Buyer's public key (not a signer — just stored for access control)
-    /// CHECK: This is the buyer's pubkey stored in the escrow state. Validated off-chain.
-    pub buyer: UncheckedAccount<'info>,
+    /// Beneficiary's public key (not a signer — just stored for access control)
+    /// CHECK: This is the beneficiary's pubkey stored in the escrow state. Validated off-chain.
+    pub beneficiary: UncheckedAccount<'info>,
 
     /// Admin authority for dispute resolution — must match AUTHORIZED_ADMIN
     /// CHECK: Validated against hardcoded AUTHORIZED_ADMIN constant in handler.
@@ -25,12 +35,20 @@ pub struct InitializeEscrow<'info> {
     /// Token mint being escrowed
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// Program config — no new escrows while paused
+    #[account(
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
     /// Escrow state PDA
     #[account(
         init,
-        payer = seller,
+        payer = rent_payer,
         space = 8 + EscrowState::INIT_SPACE,
-        seeds = [b"escrow", trade_id.as_ref(), seller.key().as_ref()],
+        seeds = [b"escrow", trade_id.as_ref(), depositor.key().as_ref()],
         bump,
     )]
     pub escrow_state: Account<'info, EscrowState>,
@@ -38,7 +56,7 @@ pub struct InitializeEscrow<'info> {
     /// Vault token account owned by the escrow PDA
     #[account(
         init,
-        payer = seller,
+        payer = rent_payer,
         token::mint = mint,
         token::authority = escrow_state,
         token::token_program = token_program,
@@ -47,30 +65,190 @@ pub struct InitializeEscrow<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// Seller's token account to transfer from
+    /// Depositor's token account to transfer from
     #[account(
         mut,
         token::mint = mint,
-        token::authority = seller,
+        token::authority = depositor,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Depositor's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", depositor.key().as_ref()],
+        bump,
+        constraint = depositor_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
     )]
-    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub depositor_denylist: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", beneficiary.key().as_ref()],
+        bump,
+        constraint = beneficiary_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub beneficiary_denylist: UncheckedAccount<'info>,
+
+    /// CHECK: The mint's policy PDA — may not exist, in which case the mint is ungated
+    #[account(
+        seeds = [b"mint_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    /// Depositor's rolling volume in this mint — required while the mint caps their volume,
+    /// omit otherwise
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", depositor.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub depositor_volume: Option<Box<Account<'info, VolumeTracker>>>,
+
+    /// Beneficiary's rolling volume in this mint — required while the mint caps their volume,
+    /// omit otherwise
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", beneficiary.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub beneficiary_volume: Option<Box<Account<'info, VolumeTracker>>>,
+
+    /// Per-mint statistics, created with the mint's first escrow
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
 
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+
+    /// Rent sponsor pool reimbursing the operator — omit unless the pool fronts the rent
+    #[account(
+        mut,
+        seeds = [b"rent_sponsor"],
+        bump = rent_sponsor.bump,
+    )]
+    pub rent_sponsor: Option<Account<'info, RentSponsor>>,
+
+    /// Token account the depositor wants refunds to land in instead of their own — omit
+    /// to refund into their own token account
+    pub refund_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's KYC attestation — required when the amount is above the mint's threshold
+    #[account(
+        seeds = [b"kyc", depositor.key().as_ref()],
+        bump = depositor_kyc.bump,
+    )]
+    pub depositor_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `depositor_kyc` — the attestation only counts
+    /// while its attestor is registered
+    #[account(
+        seeds = [b"kyc_attestor", depositor_kyc_attestor.authority.as_ref()],
+        bump = depositor_kyc_attestor.bump,
+    )]
+    pub depositor_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Beneficiary's KYC attestation — required when the amount is above the mint's threshold
+    #[account(
+        seeds = [b"kyc", beneficiary.key().as_ref()],
+        bump = beneficiary_kyc.bump,
+    )]
+    pub beneficiary_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `beneficiary_kyc`
+    #[account(
+        seeds = [b"kyc_attestor", beneficiary_kyc_attestor.authority.as_ref()],
+        bump = beneficiary_kyc_attestor.bump,
+    )]
+    pub beneficiary_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Registered referrer of the depositor — omit when they have none
+    #[account(
+        mut,
+        seeds = [b"referrer", depositor_referrer.referrer.as_ref(), mint.key().as_ref()],
+        bump = depositor_referrer.bump,
+    )]
+    pub depositor_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Registered referrer of the beneficiary — omit when they have none
+    #[account(
+        mut,
+        seeds = [b"referrer", beneficiary_referrer.referrer.as_ref(), mint.key().as_ref()],
+        bump = beneficiary_referrer.bump,
+    )]
+    pub beneficiary_referrer: Option<Account<'info, ReferrerStats>>,
 }
 
+/// One party's rolling volume tracker and the KYC standing that picks their volume tier
+pub(crate) struct PartyVolume<'a, 'info> {
+    pub wallet: Pubkey,
+    pub tracker: Option<&'a mut Account<'info, VolumeTracker>>,
+    /// Bump of the tracker PDA when the instruction may create it
+    pub bump: Option<u8>,
+    pub kyc: Option<&'a KycAttestation>,
+    pub attestor: Option<&'a KycAttestor>,
+}
+
+/// Counts `amount` against each party's rolling volume in `mint`, capped by the policy tier
+/// of their KYC level. A tracker is only required while that tier caps anything; one passed
+/// anyway still records, so the history is there if caps are set later.
+pub(crate) fn record_volume(
+    policy: Option<&MintPolicy>,
+    mint: Pubkey,
+    parties: [PartyVolume<'_, '_>; 2],
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    for party in parties {
+        let kyc_level = party
+            .kyc
+            .filter(|kyc| kyc.is_current(party.attestor, now))
+            .map_or(0, |kyc| kyc.level);
+        let limit = policy
+            .map(|policy| policy.volume_limit(kyc_level))
+            .unwrap_or_default();
+        match party.tracker {
+            Some(tracker) => {
+                tracker.wallet = party.wallet;
+                tracker.mint = mint;
+                if let Some(bump) = party.bump {
+                    tracker.bump = bump;
+                }
+                tracker.record(amount, limit, now)?;
+            }
+            None => require!(!limit.is_capped(), EscrowError::VolumeTrackerRequired),
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_initialize(
     ctx: Context<InitializeEscrow>,
     trade_id: [u8; 16],
     amount: u64,
     timeout_seconds: u64,
+    direction: EscrowDirection,
+    terms: EscrowTerms,
+    attested_release: bool,
+    metadata: Option<TradeMetadata>,
 ) -> Result<()> {
     require!(amount > 0, EscrowError::ZeroAmount);
     require!(timeout_seconds > 0, EscrowError::ZeroTimeout);
     require!(timeout_seconds <= 30 * 24 * 3600, EscrowError::TimeoutTooLong); // Max 30 days
     require!(
-        ctx.accounts.buyer.key() != ctx.accounts.seller.key(),
-        EscrowError::BuyerCannotBeSeller
+        ctx.accounts.beneficiary.key() != ctx.accounts.depositor.key(),
+        EscrowError::BeneficiaryCannotBeDepositor
     );
     require!(
         ctx.accounts.admin.key() == AUTHORIZED_ADMIN,
@@ -84,37 +262,188 @@ pub fn handle_initialize(
 
     let clock = Clock::get()?;
 
+    // Large trades on a gated mint need both parties verified; smaller ones stay permissionless
+    let policy = MintPolicy::load(&ctx.accounts.mint_policy)?;
+    if let Some(policy) = &policy {
+        let parties = [
+            (ctx.accounts.depositor_kyc.as_deref(), ctx.accounts.depositor_kyc_attestor.as_deref()),
+            (
+                ctx.accounts.beneficiary_kyc.as_deref(),
+                ctx.accounts.beneficiary_kyc_attestor.as_deref(),
+            ),
+        ];
+        policy.check_kyc(amount, parties, clock.unix_timestamp)?;
+    }
+
+    // Both parties' rolling volume counts against their own cap, raised by their KYC level
+    let parties = [
+        PartyVolume {
+            wallet: ctx.accounts.depositor.key(),
+            tracker: ctx.accounts.depositor_volume.as_deref_mut(),
+            bump: ctx.bumps.depositor_volume,
+            kyc: ctx.accounts.depositor_kyc.as_deref(),
+            attestor: ctx.accounts.depositor_kyc_attestor.as_deref(),
+        },
+        PartyVolume {
+            wallet: ctx.accounts.beneficiary.key(),
+            tracker: ctx.accounts.beneficiary_volume.as_deref_mut(),
+            bump: ctx.bumps.beneficiary_volume,
+            kyc: ctx.accounts.beneficiary_kyc.as_deref(),
+            attestor: ctx.accounts.beneficiary_kyc_attestor.as_deref(),
+        },
+    ];
+    record_volume(
+        policy.as_ref(),
+        ctx.accounts.mint.key(),
+        parties,
+        amount,
+        clock.unix_timestamp,
+    )?;
+
+    let (mode, milestones) = match terms {
+        EscrowTerms::Standard => (EscrowMode::Standard, Vec::new()),
+        EscrowTerms::Milestones { milestones } => {
+            require!(
+                !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+                EscrowError::InvalidMilestoneCount
+            );
+            let mut total: u64 = 0;
+            for milestone in milestones.iter() {
+                require!(milestone.amount > 0, EscrowError::MilestoneAmountMismatch);
+                if let Some(deadline) = milestone.deadline {
+                    require!(
+                        deadline > clock.unix_timestamp,
+                        EscrowError::InvalidMilestoneDeadline
+                    );
+                }
+                total = total
+                    .checked_add(milestone.amount)
+                    .ok_or(EscrowError::MilestoneAmountMismatch)?;
+            }
+            require!(total == amount, EscrowError::MilestoneAmountMismatch);
+            (EscrowMode::Milestones, milestones)
+        }
+        EscrowTerms::Vesting { start, end } => {
+            require!(
+                end > start && end > clock.unix_timestamp,
+                EscrowError::InvalidVestingSchedule
+            );
+            (EscrowMode::Vesting { start, end, total: amount }, Vec::new())
+        }
+        EscrowTerms::Hashlock { algorithm, hash } => {
+            (EscrowMode::Hashlock { algorithm, hash }, Vec::new())
+        }
+    };
+    let milestone_count = milestones.len() as u8;
+
+    // A sponsored escrow's rent is fronted by the operator and reimbursed from the pool
+    // right away, so the rent flows back to the pool when the escrow closes
+    let rent_payer = match ctx.accounts.rent_sponsor.as_mut() {
+        Some(sponsor) => {
+            require!(
+                sponsor.operator == ctx.accounts.rent_payer.key(),
+                EscrowError::InvalidRentPayer
+            );
+            let lamports = ctx.accounts.escrow_state.get_lamports()
+                + ctx.accounts.vault.get_lamports();
+            let reserve = Rent::get()?.minimum_balance(8 + RentSponsor::INIT_SPACE);
+            require!(
+                sponsor.get_lamports() >= reserve + lamports,
+                EscrowError::RentSponsorDepleted
+            );
+            sponsor.sub_lamports(lamports)?;
+            ctx.accounts.rent_payer.add_lamports(lamports)?;
+            sponsor.total_fronted = sponsor.total_fronted.saturating_add(lamports);
+
+            emit!(RentSponsored {
+                trade_id,
+                sponsor: sponsor.key(),
+                lamports,
+            });
+            sponsor.key()
+        }
+        None => ctx.accounts.rent_payer.key(),
+    };
+
     // Initialize escrow state
     let escrow = &mut ctx.accounts.escrow_state;
     escrow.trade_id = trade_id;
-    escrow.seller = ctx.accounts.seller.key();
-    escrow.buyer = ctx.accounts.buyer.key();
+    escrow.depositor = ctx.accounts.depositor.key();
+    escrow.beneficiary = ctx.accounts.beneficiary.key();
     escrow.mint = ctx.accounts.mint.key();
     escrow.amount = amount;
     escrow.status = EscrowStatus::Locked;
     escrow.admin = ctx.accounts.admin.key();
     escrow.locked_at = clock.unix_timestamp;
+    escrow.created_at = clock.unix_timestamp;
     escrow.timeout_seconds = timeout_seconds;
     escrow.bump = ctx.bumps.escrow_state;
+    escrow.direction = direction;
+    escrow.mode = mode;
+    escrow.milestones = milestones;
+    escrow.next_milestone = 0;
+    escrow.attested_release = attested_release;
+    escrow.rent_payer = rent_payer;
+    escrow.payout_account = None;
+    escrow.metadata = metadata;
+    escrow.payment_reference = None;
+    escrow.payment_reference_updated = false;
+    escrow.version = ESCROW_VERSION;
+    let parties = [escrow.depositor, escrow.beneficiary];
+    escrow.depositor_referrer = match ctx.accounts.depositor_referrer.as_mut() {
+        Some(referrer) => Some(referrer.refer(parties)?),
+        None => None,
+    };
+    escrow.beneficiary_referrer = match ctx.accounts.beneficiary_referrer.as_mut() {
+        Some(referrer) => Some(referrer.refer(parties)?),
+        None => None,
+    };
+    // Both sides naming the same referrer would credit one stats account twice in a transaction
+    if let Some(referrer) = escrow.depositor_referrer {
+        require!(
+            escrow.beneficiary_referrer != Some(referrer),
+            EscrowError::InvalidReferrer
+        );
+    }
+    escrow.refund_account = None;
+    if let Some(refund) = ctx.accounts.refund_token_account.as_ref() {
+        escrow.check_destination(refund)?;
+        escrow.refund_account = Some(refund.key());
 
-    // Transfer tokens from seller to vault
+        emit!(RefundAccountSet {
+            trade_id,
+            depositor: escrow.depositor,
+            account: refund.key(),
+        });
+    }
+
+    // Transfer tokens from depositor to vault
     let cpi_accounts = TransferChecked {
-        from: ctx.accounts.seller_token_account.to_account_info(),
+        from: ctx.accounts.depositor_token_account.to_account_info(),
         to: ctx.accounts.vault.to_account_info(),
-        authority: ctx.accounts.seller.to_account_info(),
+        authority: ctx.accounts.depositor.to_account_info(),
         mint: ctx.accounts.mint.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
     transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
 
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.opened(amount, policy.as_ref().and_then(|policy| policy.tvl_cap))?;
+
     emit!(EscrowInitialized {
         trade_id,
-        seller: ctx.accounts.seller.key(),
-        buyer: ctx.accounts.buyer.key(),
+        depositor: ctx.accounts.depositor.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
         mint: ctx.accounts.mint.key(),
         amount,
         timeout_seconds,
+        direction,
+        milestone_count,
+        rent_payer,
+        metadata,
     });
 
     msg!("Escrow initialized: {} tokens locked", amount);
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, ConfigInitialized, DEFAULT_EMERGENCY_GRACE_PERIOD};
+use crate::AUTHORIZED_ADMIN;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// Admin (must match AUTHORIZED_ADMIN) — only needed once, to hand roles out
+    #[account(
+        mut,
+        constraint = admin.key() == AUTHORIZED_ADMIN @ EscrowError::InvalidAdmin,
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_config(ctx: Context<InitializeConfig>, config_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.config_admin = config_admin;
+    config.pauser = None;
+    config.arbiter = None;
+    config.fee_manager = None;
+    config.mint_manager = None;
+    config.paused = false;
+    config.paused_at = 0;
+    config.bump = ctx.bumps.config;
+    config.config_delay = 0;
+    config.next_change_id = 0;
+    config.emergency_grace_period = DEFAULT_EMERGENCY_GRACE_PERIOD;
+    config.fee_bps = 0;
+    config.referral_share_bps = 0;
+    config.fee_recipient = Pubkey::default();
+
+    emit!(ConfigInitialized { config_admin });
+
+    msg!("Config initialized, config admin {}", config_admin);
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::GlobalStats;
+
+#[derive(Accounts)]
+pub struct InitializeGlobalStats<'info> {
+    /// Anyone — pays for the stats account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_global_stats(ctx: Context<InitializeGlobalStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+
+    msg!("Global stats created for {}", stats.mint);
+    Ok(())
+}
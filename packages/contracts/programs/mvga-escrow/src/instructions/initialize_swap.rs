@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{Config, GlobalStats, MintPolicy, SwapCreated, SwapState};
+
+#[derive(Accounts)]
+#[instruction(swap_id: [u8; 16])]
+pub struct InitializeSwap<'info> {
+    /// Maker who locks token A
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Token the maker deposits
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    /// Token the maker wants in return
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    /// Program config — no new swaps while paused
+    #[account(
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Swap state PDA
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + SwapState::INIT_SPACE,
+        seeds = [b"swap", swap_id.as_ref(), maker.key().as_ref()],
+        bump,
+    )]
+    pub swap_state: Account<'info, SwapState>,
+
+    /// Vault token account owned by the swap PDA
+    #[account(
+        init,
+        payer = maker,
+        token::mint = mint_a,
+        token::authority = swap_state,
+        token::token_program = token_program_a,
+        seeds = [b"vault", swap_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Maker's token A account to transfer from
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = maker,
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Maker's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", maker.key().as_ref()],
+        bump,
+        constraint = maker_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub maker_denylist: UncheckedAccount<'info>,
+
+    /// Per-mint statistics — the maker's leg counts towards its TVL cap
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint_a.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// CHECK: The mint's policy PDA — may not exist, in which case the mint is uncapped
+    #[account(
+        seeds = [b"mint_policy", mint_a.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_swap(
+    ctx: Context<InitializeSwap>,
+    swap_id: [u8; 16],
+    amount_a: u64,
+    amount_b: u64,
+    taker: Option<Pubkey>,
+) -> Result<()> {
+    require!(amount_a > 0 && amount_b > 0, EscrowError::ZeroAmount);
+    require!(
+        ctx.accounts.mint_a.key() != ctx.accounts.mint_b.key(),
+        EscrowError::SwapMintsMustDiffer
+    );
+    require!(
+        taker != Some(ctx.accounts.maker.key()),
+        EscrowError::BeneficiaryCannotBeDepositor
+    );
+    // Reject mints with freeze authority on either leg — prevents funds from being frozen
+    require!(
+        ctx.accounts.mint_a.freeze_authority.is_none(),
+        EscrowError::MintHasFreezeAuthority
+    );
+    require!(
+        ctx.accounts.mint_b.freeze_authority.is_none(),
+        EscrowError::MintHasFreezeAuthority
+    );
+
+    let clock = Clock::get()?;
+
+    // Initialize swap state
+    let swap = &mut ctx.accounts.swap_state;
+    swap.swap_id = swap_id;
+    swap.maker = ctx.accounts.maker.key();
+    swap.taker = taker;
+    swap.mint_a = ctx.accounts.mint_a.key();
+    swap.mint_b = ctx.accounts.mint_b.key();
+    swap.amount_a = amount_a;
+    swap.amount_b = amount_b;
+    swap.created_at = clock.unix_timestamp;
+    swap.bump = ctx.bumps.swap_state;
+
+    // Transfer token A from maker to vault
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.maker_token_account_a.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.maker.to_account_info(),
+        mint: ctx.accounts.mint_a.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program_a.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer_checked(cpi_ctx, amount_a, ctx.accounts.mint_a.decimals)?;
+
+    // The maker's leg sits in a program vault until taken, so it is capped like an escrow
+    let policy = MintPolicy::load(&ctx.accounts.mint_policy)?;
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint_a.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.deposited(amount_a, policy.and_then(|policy| policy.tvl_cap))?;
+
+    emit!(SwapCreated {
+        swap_id,
+        maker: ctx.accounts.maker.key(),
+        taker,
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        amount_a,
+        amount_b,
+    });
+
+    msg!("Swap created: {} tokens locked for {} in return", amount_a, amount_b);
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{KycAttestation, KycAttested, KycAttestor};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct IssueKycAttestation<'info> {
+    /// Registered KYC attestor — pays for the attestation account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"kyc_attestor", authority.key().as_ref()],
+        bump = kyc_attestor.bump,
+    )]
+    pub kyc_attestor: Account<'info, KycAttestor>,
+
+    /// One attestation per wallet — re-issuing replaces the level and expiry
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + KycAttestation::INIT_SPACE,
+        seeds = [b"kyc", wallet.as_ref()],
+        bump,
+    )]
+    pub kyc_attestation: Account<'info, KycAttestation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_issue_kyc_attestation(
+    ctx: Context<IssueKycAttestation>,
+    wallet: Pubkey,
+    level: u8,
+    expires_at: i64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at > now, EscrowError::InvalidKycExpiry);
+
+    let attestation = &mut ctx.accounts.kyc_attestation;
+    attestation.wallet = wallet;
+    attestation.attestor = ctx.accounts.authority.key();
+    attestation.level = level;
+    attestation.issued_at = now;
+    attestation.expires_at = expires_at;
+    attestation.bump = ctx.bumps.kyc_attestation;
+
+    emit!(KycAttested {
+        wallet,
+        attestor: attestation.attestor,
+        level,
+        expires_at,
+    });
+
+    msg!("KYC level {} attested for {} until {}", level, wallet, expires_at);
+    Ok(())
+}
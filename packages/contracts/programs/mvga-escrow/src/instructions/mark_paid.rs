@@ -1,33 +1,98 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
 
 use crate::errors::EscrowError;
-use crate::state::{EscrowState, EscrowStatus, PaymentMarked};
+use crate::state::{
+    EscrowState, EscrowStatus, GlobalStats, PaymentMarked, PaymentReference, PayoutAccountSet,
+    SessionKey, SESSION_MARK_PAID,
+};
 
 #[derive(Accounts)]
 pub struct MarkPaid<'info> {
-    /// Buyer who marks the off-chain payment as sent
-    pub buyer: Signer<'info>,
+    /// Beneficiary who marks the off-chain leg (payment or delivery) as done,
+    /// or a session delegate acting for them
+    pub authority: Signer<'info>,
 
     /// Escrow state — must be in Locked status
     #[account(
         mut,
-        constraint = escrow_state.buyer == buyer.key() @ EscrowError::UnauthorizedBuyer,
         constraint = escrow_state.status == EscrowStatus::Locked @ EscrowError::InvalidStatus,
-        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.seller.as_ref()],
+        constraint = escrow_state.mode.has_offchain_leg() @ EscrowError::InvalidMode,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
         bump = escrow_state.bump,
     )]
     pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// CHECK: Beneficiary's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", escrow_state.beneficiary.as_ref()],
+        bump,
+        constraint = beneficiary_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub beneficiary_denylist: UncheckedAccount<'info>,
+
+    /// Beneficiary's session key — omit when the beneficiary signs directly
+    #[account(
+        seeds = [b"session", session_key.owner.as_ref(), session_key.delegate.as_ref()],
+        bump = session_key.bump,
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+
+    /// Token account the beneficiary wants to be paid into instead of their ATA — omit to
+    /// keep the current destination. Session delegates can't pass one.
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
-pub fn handle_mark_paid(ctx: Context<MarkPaid>) -> Result<()> {
+pub fn handle_mark_paid(
+    ctx: Context<MarkPaid>,
+    payment_reference: Option<PaymentReference>,
+) -> Result<()> {
     let escrow = &mut ctx.accounts.escrow_state;
+    SessionKey::authorize(
+        ctx.accounts.session_key.as_deref(),
+        &ctx.accounts.authority.key(),
+        &escrow.beneficiary,
+        SESSION_MARK_PAID,
+        EscrowError::UnauthorizedBeneficiary,
+    )?;
+
+    if let Some(payout) = ctx.accounts.payout_token_account.as_ref() {
+        // A delegate redirecting the release would be spending beyond its mark-paid scope
+        require!(
+            ctx.accounts.authority.key() == escrow.beneficiary,
+            EscrowError::PayoutChangeNotAllowed
+        );
+        escrow.check_destination(payout)?;
+        escrow.payout_account = Some(payout.key());
+
+        emit!(PayoutAccountSet {
+            trade_id: escrow.trade_id,
+            beneficiary: escrow.beneficiary,
+            account: payout.key(),
+        });
+    }
+
+    // Each milestone is marked paid on its own, with its own reference and update allowance
+    escrow.payment_reference = payment_reference;
+    escrow.payment_reference_updated = false;
     escrow.status = EscrowStatus::PaymentSent;
+    ctx.accounts.global_stats.moved(EscrowStatus::Locked, EscrowStatus::PaymentSent);
 
     emit!(PaymentMarked {
         trade_id: escrow.trade_id,
-        buyer: ctx.accounts.buyer.key(),
+        beneficiary: escrow.beneficiary,
+        payout_account: escrow.payout_account,
+        payment_reference,
     });
 
-    msg!("Buyer marked payment as sent");
+    msg!("Beneficiary marked payment as sent");
     Ok(())
 }
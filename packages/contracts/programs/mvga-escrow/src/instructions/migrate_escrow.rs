@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+use crate::errors::EscrowError;
+use crate::state::{EscrowMigrated, EscrowState, EscrowStateV0, ESCROW_VERSION};
+
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    /// Anyone may pay for the upgrade — usually the depositor or the backend
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Escrow state on the launch layout, which `Account<EscrowState>` can't load.
+    /// Discriminator, length and PDA seeds are checked in the handler.
+    #[account(mut, owner = crate::ID)]
+    pub escrow_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+    let escrow_info = ctx.accounts.escrow_state.to_account_info();
+
+    // Only launch-layout escrows need upgrading; anything else is already current
+    let legacy = {
+        let data = escrow_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == *EscrowState::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            data.len() == 8 + EscrowStateV0::INIT_SPACE,
+            EscrowError::AlreadyMigrated
+        );
+        EscrowStateV0::deserialize(&mut &data[8..])?
+    };
+
+    let expected = Pubkey::create_program_address(
+        &[
+            b"escrow",
+            legacy.trade_id.as_ref(),
+            legacy.seller.as_ref(),
+            &[legacy.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| ErrorCode::ConstraintSeeds)?;
+    require_keys_eq!(expected, escrow_info.key(), ErrorCode::ConstraintSeeds);
+
+    // Payer tops the account up to rent exemption at the new size
+    let new_len = 8 + EscrowState::INIT_SPACE;
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(escrow_info.lamports());
+    if shortfall > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: escrow_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, shortfall)?;
+    }
+    escrow_info.resize(new_len)?;
+
+    let trade_id = legacy.trade_id;
+    let migrated = legacy.upgrade();
+    let mut data = escrow_info.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])?;
+
+    emit!(EscrowMigrated {
+        trade_id,
+        escrow: escrow_info.key(),
+        from_version: 0,
+        to_version: ESCROW_VERSION,
+    });
+
+    msg!("Escrow migrated to layout version {}", ESCROW_VERSION);
+    Ok(())
+}
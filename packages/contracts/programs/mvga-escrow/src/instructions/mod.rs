@@ -4,6 +4,53 @@ pub mod release;
 pub mod refund;
 pub mod dispute;
 pub mod resolve;
+pub mod claim_vested;
+pub mod cancel_vesting;
+pub mod claim_preimage;
+pub mod initialize_swap;
+pub mod take_swap;
+pub mod cancel_swap;
+pub mod create_offer;
+pub mod take_offer;
+pub mod top_up_offer;
+pub mod withdraw_offer;
+pub mod close_offer;
+pub mod fill_signed_order;
+pub mod cancel_signed_order;
+pub mod release_voucher;
+pub mod register_attestor;
+pub mod remove_attestor;
+pub mod release_attested;
+pub mod create_session_key;
+pub mod revoke_session_key;
+pub mod configure_rent_sponsor;
+pub mod fund_rent_sponsor;
+pub mod set_refund_account;
+pub mod amend_escrow;
+pub mod extend_timeout;
+pub mod update_payment_reference;
+pub mod migrate_escrow;
+pub mod initialize_config;
+pub mod grant_role;
+pub mod revoke_role;
+pub mod pause;
+pub mod propose_config_change;
+pub mod execute_config_change;
+pub mod cancel_config_change;
+pub mod emergency_withdraw;
+pub mod add_to_denylist;
+pub mod remove_from_denylist;
+pub mod dispute_denylisted_escrow;
+pub mod register_kyc_attestor;
+pub mod remove_kyc_attestor;
+pub mod issue_kyc_attestation;
+pub mod revoke_kyc_attestation;
+pub mod set_kyc_requirement;
+pub mod set_volume_limits;
+pub mod set_tvl_cap;
+pub mod initialize_global_stats;
+pub mod set_fees;
+pub mod register_referrer;
 
 pub use initialize::*;
 pub use mark_paid::*;
@@ -11,3 +58,50 @@ pub use release::*;
 pub use refund::*;
 pub use dispute::*;
 pub use resolve::*;
+pub use claim_vested::*;
+pub use cancel_vesting::*;
+pub use claim_preimage::*;
+pub use initialize_swap::*;
+pub use take_swap::*;
+pub use cancel_swap::*;
+pub use create_offer::*;
+pub use take_offer::*;
+pub use top_up_offer::*;
+pub use withdraw_offer::*;
+pub use close_offer::*;
+pub use fill_signed_order::*;
+pub use cancel_signed_order::*;
+pub use release_voucher::*;
+pub use register_attestor::*;
+pub use remove_attestor::*;
+pub use release_attested::*;
+pub use create_session_key::*;
+pub use revoke_session_key::*;
+pub use configure_rent_sponsor::*;
+pub use fund_rent_sponsor::*;
+pub use set_refund_account::*;
+pub use amend_escrow::*;
+pub use extend_timeout::*;
+pub use update_payment_reference::*;
+pub use migrate_escrow::*;
+pub use initialize_config::*;
+pub use grant_role::*;
+pub use revoke_role::*;
+pub use pause::*;
+pub use propose_config_change::*;
+pub use execute_config_change::*;
+pub use cancel_config_change::*;
+pub use emergency_withdraw::*;
+pub use add_to_denylist::*;
+pub use remove_from_denylist::*;
+pub use dispute_denylisted_escrow::*;
+pub use register_kyc_attestor::*;
+pub use remove_kyc_attestor::*;
+pub use issue_kyc_attestation::*;
+pub use revoke_kyc_attestation::*;
+pub use set_kyc_requirement::*;
+pub use set_volume_limits::*;
+pub use set_tvl_cap::*;
+pub use initialize_global_stats::*;
+pub use set_fees::*;
+pub use register_referrer::*;
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, ProgramPaused, ProgramUnpaused, Role};
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    /// Pauser hot key (the config admin may also pause)
+    pub pauser: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.has_role(Role::Pauser, &pauser.key())
+            || config.has_role(Role::ConfigAdmin, &pauser.key()) @ EscrowError::MissingRole,
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    /// Only the config admin lifts a pause
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        constraint = config.paused @ EscrowError::NotPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handle_pause(ctx: Context<Pause>) -> Result<()> {
+    let paused_at = Clock::get()?.unix_timestamp;
+    let config = &mut ctx.accounts.config;
+    config.paused = true;
+    config.paused_at = paused_at;
+
+    emit!(ProgramPaused {
+        pauser: ctx.accounts.pauser.key(),
+        paused_at,
+    });
+
+    msg!("Program paused");
+    Ok(())
+}
+
+pub fn handle_unpause(ctx: Context<Unpause>) -> Result<()> {
+    ctx.accounts.config.paused = false;
+
+    emit!(ProgramUnpaused {
+        config_admin: ctx.accounts.config_admin.key(),
+    });
+
+    msg!("Program unpaused");
+    Ok(())
+}
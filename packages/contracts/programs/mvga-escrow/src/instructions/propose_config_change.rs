@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, ConfigChange, ConfigChangeProposed, PendingConfigChange, Role};
+
+#[derive(Accounts)]
+pub struct ProposeConfigChange<'info> {
+    /// Current config admin — pays for the pending change account
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = config_admin,
+        space = 8 + PendingConfigChange::INIT_SPACE,
+        seeds = [b"config_change", config.next_change_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_propose_config_change(
+    ctx: Context<ProposeConfigChange>,
+    change: ConfigChange,
+) -> Result<()> {
+    // Reject changes that could never execute instead of letting them sit in the queue
+    change.validate()?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let config = &mut ctx.accounts.config;
+    let id = config.next_change_id;
+    let executable_at = now + config.config_delay;
+    config.next_change_id += 1;
+
+    let pending = &mut ctx.accounts.pending_change;
+    pending.id = id;
+    pending.change = change;
+    pending.proposer = ctx.accounts.config_admin.key();
+    pending.proposed_at = now;
+    pending.executable_at = executable_at;
+    pending.bump = ctx.bumps.pending_change;
+
+    emit!(ConfigChangeProposed {
+        id,
+        change,
+        proposer: pending.proposer,
+        executable_at,
+    });
+
+    msg!("Config change {} proposed, executable at {}", id, executable_at);
+    Ok(())
+}
@@ -5,13 +5,20 @@ use anchor_spl::token_interface::{
 };
 
 use crate::errors::EscrowError;
-use crate::state::{EscrowRefunded, EscrowState, EscrowStatus};
+use crate::state::{EscrowRefunded, EscrowState, EscrowStatus, GlobalStats};
 
 #[derive(Accounts)]
 pub struct RefundEscrow<'info> {
-    /// Seller requesting the refund (must be the original seller)
+    /// Depositor requesting the refund (must be the original depositor)
     #[account(mut)]
-    pub seller: Signer<'info>,
+    pub depositor: Signer<'info>,
+
+    /// CHECK: Whoever paid the escrow's rent gets it back when the escrow closes
+    #[account(
+        mut,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
 
     #[account(
         constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
@@ -20,13 +27,21 @@ pub struct RefundEscrow<'info> {
 
     #[account(
         mut,
-        close = seller,
-        constraint = escrow_state.seller == seller.key() @ EscrowError::UnauthorizedSeller,
-        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.seller.as_ref()],
+        close = rent_payer,
+        constraint = escrow_state.depositor == depositor.key() @ EscrowError::UnauthorizedDepositor,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
         bump = escrow_state.bump,
     )]
     pub escrow_state: Account<'info, EscrowState>,
 
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
     /// Vault holding escrowed tokens
     #[account(
         mut,
@@ -38,13 +53,17 @@ pub struct RefundEscrow<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// Seller's token account to receive refund
+    /// Depositor's token account to receive refund — omit when they chose a refund account
     #[account(
         mut,
         token::mint = mint,
-        token::authority = seller,
+        token::authority = depositor,
     )]
-    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub depositor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Refund account the depositor chose at init or through `set_refund_account`
+    #[account(mut)]
+    pub refund_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -53,14 +72,14 @@ pub struct RefundEscrow<'info> {
 pub fn handle_refund(ctx: Context<RefundEscrow>) -> Result<()> {
     let escrow = &ctx.accounts.escrow_state;
 
-    // Seller can only self-refund if status is Locked AND timeout has elapsed
+    // Vesting escrows are unwound through cancel_vesting
+    require!(!escrow.mode.is_vesting(), EscrowError::InvalidMode);
+
+    // Depositor can only self-refund if status is Locked AND timeout has elapsed
     match escrow.status {
         EscrowStatus::Locked => {
             let clock = Clock::get()?;
-            let deadline = escrow
-                .locked_at
-                .checked_add(escrow.timeout_seconds as i64)
-                .ok_or(EscrowError::TimeoutOverflow)?;
+            let deadline = escrow.refund_deadline()?;
             require!(clock.unix_timestamp >= deadline, EscrowError::NotTimedOut);
         }
         // Disputed status refund is handled by resolve_dispute (admin only)
@@ -70,20 +89,25 @@ pub fn handle_refund(ctx: Context<RefundEscrow>) -> Result<()> {
     let amount = escrow.amount;
     let decimals = ctx.accounts.mint.decimals;
 
+    let destination = escrow.refund_destination(
+        ctx.accounts.depositor_token_account.as_ref(),
+        ctx.accounts.refund_token_account.as_ref(),
+    )?;
+
     let trade_id = escrow.trade_id;
-    let seller_key = escrow.seller;
+    let depositor_key = escrow.depositor;
     let bump = escrow.bump;
     let signer_seeds: &[&[&[u8]]] = &[&[
         b"escrow",
         trade_id.as_ref(),
-        seller_key.as_ref(),
+        depositor_key.as_ref(),
         &[bump],
     ]];
 
-    // Transfer tokens from vault back to seller
+    // Transfer tokens from vault back to depositor
     let cpi_accounts = TransferChecked {
         from: ctx.accounts.vault.to_account_info(),
-        to: ctx.accounts.seller_token_account.to_account_info(),
+        to: destination.to_account_info(),
         authority: ctx.accounts.escrow_state.to_account_info(),
         mint: ctx.accounts.mint.to_account_info(),
     };
@@ -94,7 +118,7 @@ pub fn handle_refund(ctx: Context<RefundEscrow>) -> Result<()> {
     // Close vault account
     let close_accounts = CloseAccount {
         account: ctx.accounts.vault.to_account_info(),
-        destination: ctx.accounts.seller.to_account_info(),
+        destination: ctx.accounts.rent_payer.to_account_info(),
         authority: ctx.accounts.escrow_state.to_account_info(),
     };
     let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
@@ -105,12 +129,16 @@ pub fn handle_refund(ctx: Context<RefundEscrow>) -> Result<()> {
     let escrow = &mut ctx.accounts.escrow_state;
     escrow.status = EscrowStatus::Refunded;
 
+    let stats = &mut ctx.accounts.global_stats;
+    stats.returned(amount);
+    stats.moved(EscrowStatus::Locked, EscrowStatus::Refunded);
+
     emit!(EscrowRefunded {
         trade_id,
-        seller: seller_key,
+        depositor: depositor_key,
         amount,
     });
 
-    msg!("Escrow refunded: {} tokens returned to seller", amount);
+    msg!("Escrow refunded: {} tokens returned to depositor", amount);
     Ok(())
 }
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Attestor, AttestorRegistered, Config, Role};
+
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct RegisterAttestor<'info> {
+    /// Config admin
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = config_admin,
+        space = 8 + Attestor::INIT_SPACE,
+        seeds = [b"attestor", authority.as_ref()],
+        bump,
+    )]
+    pub attestor: Account<'info, Attestor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_register_attestor(ctx: Context<RegisterAttestor>, authority: Pubkey) -> Result<()> {
+    // Payment attestors can release escrows on their own, like the arbiter. Once a config
+    // delay is set, it has to be lowered through the queue before this can run
+    require!(ctx.accounts.config.config_delay == 0, EscrowError::TimelockRequired);
+
+    let attestor = &mut ctx.accounts.attestor;
+    attestor.authority = authority;
+    attestor.registered_at = Clock::get()?.unix_timestamp;
+    attestor.bump = ctx.bumps.attestor;
+
+    emit!(AttestorRegistered {
+        attestor: authority,
+    });
+
+    msg!("Attestor registered: {}", authority);
+    Ok(())
+}
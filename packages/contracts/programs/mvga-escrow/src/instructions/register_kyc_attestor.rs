@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, KycAttestor, KycAttestorRegistered, Role};
+
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct RegisterKycAttestor<'info> {
+    /// Config admin
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = config_admin,
+        space = 8 + KycAttestor::INIT_SPACE,
+        seeds = [b"kyc_attestor", authority.as_ref()],
+        bump,
+    )]
+    pub kyc_attestor: Account<'info, KycAttestor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_register_kyc_attestor(
+    ctx: Context<RegisterKycAttestor>,
+    authority: Pubkey,
+) -> Result<()> {
+    // KYC attestors decide who may trade above the threshold. Once a config
+    // delay is set, it has to be lowered through the queue before this can run
+    require!(ctx.accounts.config.config_delay == 0, EscrowError::TimelockRequired);
+
+    let attestor = &mut ctx.accounts.kyc_attestor;
+    attestor.authority = authority;
+    attestor.registered_at = Clock::get()?.unix_timestamp;
+    attestor.bump = ctx.bumps.kyc_attestor;
+
+    emit!(KycAttestorRegistered {
+        attestor: authority,
+    });
+
+    msg!("KYC attestor registered: {}", authority);
+    Ok(())
+}
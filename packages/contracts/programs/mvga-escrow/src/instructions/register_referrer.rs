@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{ReferrerRegistered, ReferrerStats};
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    /// Wallet signing up to refer traders of `mint`
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + ReferrerStats::INIT_SPACE,
+        seeds = [b"referrer", referrer.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub referrer_stats: Account<'info, ReferrerStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+    let stats = &mut ctx.accounts.referrer_stats;
+    stats.referrer = ctx.accounts.referrer.key();
+    stats.mint = ctx.accounts.mint.key();
+    stats.referrals = 0;
+    stats.referred_volume = 0;
+    stats.earnings = 0;
+    stats.bump = ctx.bumps.referrer_stats;
+
+    emit!(ReferrerRegistered {
+        referrer: stats.referrer,
+        mint: stats.mint,
+    });
+
+    msg!("Referrer {} registered for {}", stats.referrer, stats.mint);
+    Ok(())
+}
@@ -6,36 +6,66 @@ use anchor_spl::token_interface::{
 };
 
 use crate::errors::EscrowError;
-use crate::state::{EscrowReleased, EscrowState, EscrowStatus};
+use crate::state::{
+    Config, EscrowMode, EscrowReleased, EscrowState, EscrowStatus, GlobalStats,
+    MilestoneReleased, PlatformFeeCollected, ReferralPaid, ReferrerStats, SessionKey,
+    SESSION_RELEASE,
+};
 
 #[derive(Accounts)]
 pub struct ReleaseEscrow<'info> {
-    /// Seller confirms they received off-chain payment
+    /// Depositor confirming the off-chain leg was completed, or a session delegate
+    /// acting for them
+    pub authority: Signer<'info>,
+
+    /// Pays fees and the beneficiary's token account rent if it doesn't exist — the
+    /// authority itself or a relayer
     #[account(mut)]
-    pub seller: Signer<'info>,
+    pub payer: Signer<'info>,
 
-    /// CHECK: Buyer receives the escrowed tokens
+    /// CHECK: Whoever paid the escrow's rent gets it back
     #[account(
         mut,
-        constraint = buyer.key() == escrow_state.buyer @ EscrowError::UnauthorizedBuyer,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
     )]
-    pub buyer: UncheckedAccount<'info>,
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary receives the escrowed tokens
+    #[account(
+        mut,
+        constraint = beneficiary.key() == escrow_state.beneficiary @ EscrowError::UnauthorizedBeneficiary,
+    )]
+    pub beneficiary: UncheckedAccount<'info>,
 
     #[account(
         constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
     )]
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// Program config — holds the platform fee settings
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Closed to the rent payer once the full amount has been released
     #[account(
         mut,
-        close = seller,
-        constraint = escrow_state.seller == seller.key() @ EscrowError::UnauthorizedSeller,
         constraint = escrow_state.status == EscrowStatus::PaymentSent @ EscrowError::InvalidStatus,
-        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.seller.as_ref()],
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
         bump = escrow_state.bump,
     )]
     pub escrow_state: Account<'info, EscrowState>,
 
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
     /// Vault holding the escrowed tokens
     #[account(
         mut,
@@ -47,69 +77,324 @@ pub struct ReleaseEscrow<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// Buyer's token account to receive funds
+    /// Beneficiary's associated token account — omit when they chose a payout account
     #[account(
         init_if_needed,
-        payer = seller,
+        payer = payer,
         associated_token::mint = mint,
-        associated_token::authority = buyer,
+        associated_token::authority = beneficiary,
         associated_token::token_program = token_program,
     )]
-    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub beneficiary_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Payout account the beneficiary chose at acceptance or `mark_paid`
+    #[account(mut)]
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    /// Depositor's session key — omit when the depositor signs directly
+    #[account(
+        mut,
+        seeds = [b"session", session_key.owner.as_ref(), session_key.delegate.as_ref()],
+        bump = session_key.bump,
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+
+    /// Fee recipient's token account — required when the release carries a platform fee
+    #[account(mut)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", depositor_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = depositor_referrer.bump,
+    )]
+    pub depositor_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the depositor's referrer is paid its fee share into
+    #[account(mut)]
+    pub depositor_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Beneficiary's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", beneficiary_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = beneficiary_referrer.bump,
+    )]
+    pub beneficiary_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the beneficiary's referrer is paid its fee share into
+    #[account(mut)]
+    pub beneficiary_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
 pub fn handle_release(ctx: Context<ReleaseEscrow>) -> Result<()> {
-    let escrow = &ctx.accounts.escrow_state;
-    let amount = escrow.amount;
-    let decimals = ctx.accounts.mint.decimals;
+    let accounts = ctx.accounts;
+    let escrow = &accounts.escrow_state;
+    let authority = accounts.authority.key();
+    SessionKey::authorize(
+        accounts.session_key.as_deref(),
+        &authority,
+        &escrow.depositor,
+        SESSION_RELEASE,
+        EscrowError::UnauthorizedDepositor,
+    )?;
+
+    // Delegated releases count against the session's spend cap
+    if authority != escrow.depositor {
+        let amount = escrow.next_release_amount()?;
+        if let Some(session) = accounts.session_key.as_mut() {
+            session.spent = session
+                .spent
+                .checked_add(amount)
+                .filter(|spent| *spent <= session.spend_cap)
+                .ok_or(EscrowError::SessionSpendCapExceeded)?;
+        }
+    }
+
+    let destination = accounts.escrow_state.payout_destination(
+        accounts.beneficiary_token_account.as_ref(),
+        accounts.payout_token_account.as_ref(),
+    )?;
+    release_next(
+        &mut accounts.escrow_state,
+        &mut accounts.global_stats,
+        FeeAccounts {
+            config: &accounts.config,
+            fee_token_account: accounts.fee_token_account.as_ref(),
+            referrers: [
+                ReferrerAccounts {
+                    stats: accounts.depositor_referrer.as_mut(),
+                    token_account: accounts.depositor_referrer_token_account.as_ref(),
+                },
+                ReferrerAccounts {
+                    stats: accounts.beneficiary_referrer.as_mut(),
+                    token_account: accounts.beneficiary_referrer_token_account.as_ref(),
+                },
+            ],
+        },
+        &accounts.vault,
+        destination,
+        &accounts.mint,
+        &accounts.token_program,
+        accounts.rent_payer.to_account_info(),
+        None,
+    )?;
+    Ok(())
+}
+
+/// Where the platform fee on a release goes
+pub(crate) struct FeeAccounts<'a, 'info> {
+    pub config: &'a Config,
+    /// Fee recipient's token account, paid whatever the referrers don't get
+    pub fee_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    /// The depositor's and the beneficiary's referrer
+    pub referrers: [ReferrerAccounts<'a, 'info>; 2],
+}
+
+/// A referrer's stats and the token account its fee share is paid into
+pub(crate) struct ReferrerAccounts<'a, 'info> {
+    pub stats: Option<&'a mut Account<'info, ReferrerStats>>,
+    pub token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Pays `amount` from the vault to the beneficiary, less the platform fee, which is split
+/// between the escrow's referrers and the fee recipient. Every payout to the beneficiary —
+/// releases, dispute resolutions, vesting claims and cancels, and hashlock claims — goes
+/// through here, so none of them skips the fee. Records the payout in `global_stats`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pay_beneficiary<'info>(
+    escrow_state: &Account<'info, EscrowState>,
+    global_stats: &mut GlobalStats,
+    fees: FeeAccounts<'_, 'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    beneficiary_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    let escrow = &**escrow_state;
+    let (fee, referral_share) = fees.config.fee_split(amount);
+
+    let trade_id = escrow.trade_id;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"escrow",
+        trade_id.as_ref(),
+        escrow.depositor.as_ref(),
+        &[escrow.bump],
+    ]];
+
+    let pay = |to: &InterfaceAccount<'info, TokenAccount>, amount: u64| {
+        let cpi_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            to: to.to_account_info(),
+            authority: escrow_state.to_account_info(),
+            mint: mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        transfer_checked(cpi_ctx, amount, mint.decimals)
+    };
+
+    // Transfer tokens from vault to beneficiary, less the platform fee
+    pay(beneficiary_token_account, amount - fee)?;
+    global_stats.paid_out(amount);
+
+    // Each referrer is credited with the volume and paid its share of the fee
+    let mut referral_paid = 0;
+    let referrers = [escrow.depositor_referrer, escrow.beneficiary_referrer];
+    for (referrer, accounts) in referrers.into_iter().zip(fees.referrers) {
+        let Some(referrer) = referrer else {
+            continue;
+        };
+        let stats = accounts
+            .stats
+            .filter(|stats| stats.referrer == referrer)
+            .ok_or(EscrowError::InvalidReferrer)?;
+        stats.referred_volume = stats.referred_volume.saturating_add(amount);
+        if referral_share == 0 {
+            continue;
+        }
+
+        let token_account = accounts
+            .token_account
+            .filter(|account| account.owner == referrer)
+            .ok_or(EscrowError::InvalidReferrer)?;
+        pay(token_account, referral_share)?;
+        stats.earnings = stats.earnings.saturating_add(referral_share);
+        referral_paid += referral_share;
+
+        emit!(ReferralPaid {
+            trade_id,
+            referrer,
+            amount: referral_share,
+            volume: amount,
+        });
+    }
+
+    if fee > 0 {
+        let to_recipient = fee - referral_paid;
+        if to_recipient > 0 {
+            let fee_token_account = fees
+                .fee_token_account
+                .filter(|account| account.owner == fees.config.fee_recipient)
+                .ok_or(EscrowError::InvalidFeeAccount)?;
+            pay(fee_token_account, to_recipient)?;
+        }
+        global_stats.collected_fee(fee);
+
+        emit!(PlatformFeeCollected {
+            trade_id,
+            mint: mint.key(),
+            fee,
+            referral_paid,
+        });
+    }
+    Ok(())
+}
+
+/// Pays out the current release step — the whole balance, or the next milestone — and
+/// once nothing is left closes the vault and escrow state, returning rent to `rent_receiver`.
+/// Shared by every path that releases to the beneficiary; `attestor` is recorded in the
+/// release event when a payment attestation triggered it. Keeps `global_stats` in step.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn release_next<'info>(
+    escrow_state: &mut Account<'info, EscrowState>,
+    global_stats: &mut GlobalStats,
+    fees: FeeAccounts<'_, 'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    beneficiary_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    rent_receiver: AccountInfo<'info>,
+    attestor: Option<Pubkey>,
+) -> Result<u64> {
+    let escrow = &*escrow_state;
+
+    // Milestone escrows release only the current milestone; everything else releases it all.
+    // Milestone amounts are non-zero, so only the last one equals the outstanding balance.
+    let amount = escrow.next_release_amount()?;
+    let is_final = amount == escrow.amount;
+    let status = escrow.status;
 
     // PDA signer seeds for the escrow state (vault authority)
     let trade_id = escrow.trade_id;
-    let seller_key = escrow.seller;
+    let depositor_key = escrow.depositor;
+    let beneficiary_key = escrow.beneficiary;
     let bump = escrow.bump;
     let signer_seeds: &[&[&[u8]]] = &[&[
         b"escrow",
         trade_id.as_ref(),
-        seller_key.as_ref(),
+        depositor_key.as_ref(),
         &[bump],
     ]];
 
-    // Transfer tokens from vault to buyer
-    let cpi_accounts = TransferChecked {
-        from: ctx.accounts.vault.to_account_info(),
-        to: ctx.accounts.buyer_token_account.to_account_info(),
-        authority: ctx.accounts.escrow_state.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
-        .with_signer(signer_seeds);
-    transfer_checked(cpi_ctx, amount, decimals)?;
+    pay_beneficiary(
+        escrow_state,
+        global_stats,
+        fees,
+        vault,
+        beneficiary_token_account,
+        mint,
+        token_program,
+        amount,
+    )?;
+
+    let escrow = &mut *escrow_state;
+    escrow.amount = escrow
+        .amount
+        .checked_sub(amount)
+        .ok_or(EscrowError::MilestoneAmountMismatch)?;
+
+    if escrow.mode == EscrowMode::Milestones {
+        let index = escrow.next_milestone;
+        escrow.next_milestone = index + 1;
 
-    // Close vault account, return rent to seller
+        emit!(MilestoneReleased {
+            trade_id,
+            index,
+            amount,
+            remaining: escrow.amount,
+        });
+    }
+
+    if !is_final {
+        // Keep the vault open and restart the clock for the next milestone
+        escrow.status = EscrowStatus::Locked;
+        global_stats.moved(status, EscrowStatus::Locked);
+        escrow.locked_at = Clock::get()?.unix_timestamp;
+
+        msg!("Milestone released: {} tokens sent to beneficiary", amount);
+        return Ok(amount);
+    }
+
+    // Close vault account, return rent
     let close_accounts = CloseAccount {
-        account: ctx.accounts.vault.to_account_info(),
-        destination: ctx.accounts.seller.to_account_info(),
-        authority: ctx.accounts.escrow_state.to_account_info(),
+        account: vault.to_account_info(),
+        destination: rent_receiver.clone(),
+        authority: escrow_state.to_account_info(),
     };
-    let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
+    let close_ctx = CpiContext::new(token_program.to_account_info(), close_accounts)
         .with_signer(signer_seeds);
     close_account(close_ctx)?;
 
-    // Update status
-    let escrow = &mut ctx.accounts.escrow_state;
-    escrow.status = EscrowStatus::Released;
+    // Update status and close escrow state, return rent
+    escrow_state.status = EscrowStatus::Released;
+    global_stats.moved(status, EscrowStatus::Released);
+    escrow_state.close(rent_receiver)?;
 
     emit!(EscrowReleased {
         trade_id,
-        seller: seller_key,
-        buyer: ctx.accounts.buyer.key(),
+        depositor: depositor_key,
+        beneficiary: beneficiary_key,
         amount,
+        attestor,
     });
 
-    msg!("Escrow released: {} tokens sent to buyer", amount);
-    Ok(())
+    msg!("Escrow released: {} tokens sent to beneficiary", amount);
+    Ok(amount)
 }
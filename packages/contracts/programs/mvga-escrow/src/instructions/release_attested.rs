@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::EscrowError;
+use crate::signature::verify_ed25519_signature;
+use crate::state::{
+    Attestor, Config, EscrowState, EscrowStatus, GlobalStats, PaymentAttestation, ReferrerStats,
+};
+
+use super::release::{release_next, FeeAccounts, ReferrerAccounts};
+
+#[derive(Accounts)]
+pub struct ReleaseWithAttestation<'info> {
+    /// Attestor backend or relayer submitting the attestation — pays fees and ATA rent
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+
+    /// Registered attestor whose signature confirms the payment
+    #[account(
+        seeds = [b"attestor", attestor.authority.as_ref()],
+        bump = attestor.bump,
+    )]
+    pub attestor: Account<'info, Attestor>,
+
+    /// CHECK: Rent payer receives escrow rent back, exactly as in `release_escrow`
+    #[account(
+        mut,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary receives the escrowed tokens
+    #[account(
+        constraint = beneficiary.key() == escrow_state.beneficiary @ EscrowError::UnauthorizedBeneficiary,
+    )]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — holds the platform fee settings
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Closed to the rent payer once the full amount has been released
+    #[account(
+        mut,
+        constraint = escrow_state.status == EscrowStatus::PaymentSent @ EscrowError::InvalidStatus,
+        constraint = escrow_state.attested_release @ EscrowError::AttestedReleaseNotEnabled,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Vault holding the escrowed tokens
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Beneficiary's associated token account — omit when they chose a payout account
+    #[account(
+        init_if_needed,
+        payer = submitter,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Payout account the beneficiary chose at acceptance or `mark_paid`
+    #[account(mut)]
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Instructions sysvar, used to find the attestor's ed25519 signature.
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    /// Fee recipient's token account — required when the release carries a platform fee
+    #[account(mut)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", depositor_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = depositor_referrer.bump,
+    )]
+    pub depositor_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the depositor's referrer is paid its fee share into
+    #[account(mut)]
+    pub depositor_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Beneficiary's referrer — required when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", beneficiary_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = beneficiary_referrer.bump,
+    )]
+    pub beneficiary_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the beneficiary's referrer is paid its fee share into
+    #[account(mut)]
+    pub beneficiary_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+pub fn handle_release_with_attestation(
+    ctx: Context<ReleaseWithAttestation>,
+    attestation: PaymentAttestation,
+) -> Result<()> {
+    let escrow = &ctx.accounts.escrow_state;
+
+    // The attestation covers exactly the release step that is about to be paid out
+    let step_amount = escrow.next_release_amount()?;
+    require!(
+        attestation.trade_id == escrow.trade_id
+            && attestation.escrow == escrow.key()
+            && attestation.created_at == escrow.created_at
+            && attestation.milestone == escrow.next_milestone
+            && attestation.amount == step_amount,
+        EscrowError::AttestationMismatch
+    );
+
+    // The ed25519 instruction right before this one must carry the attestor's signature
+    let attestor_key = ctx.accounts.attestor.authority;
+    verify_ed25519_signature(
+        &ctx.accounts.instructions.to_account_info(),
+        &attestor_key,
+        &attestation.message()?,
+    )?;
+
+    let accounts = ctx.accounts;
+    let destination = accounts.escrow_state.payout_destination(
+        accounts.beneficiary_token_account.as_ref(),
+        accounts.payout_token_account.as_ref(),
+    )?;
+    release_next(
+        &mut accounts.escrow_state,
+        &mut accounts.global_stats,
+        FeeAccounts {
+            config: &accounts.config,
+            fee_token_account: accounts.fee_token_account.as_ref(),
+            referrers: [
+                ReferrerAccounts {
+                    stats: accounts.depositor_referrer.as_mut(),
+                    token_account: accounts.depositor_referrer_token_account.as_ref(),
+                },
+                ReferrerAccounts {
+                    stats: accounts.beneficiary_referrer.as_mut(),
+                    token_account: accounts.beneficiary_referrer_token_account.as_ref(),
+                },
+            ],
+        },
+        &accounts.vault,
+        destination,
+        &accounts.mint,
+        &accounts.token_program,
+        accounts.rent_payer.to_account_info(),
+        Some(attestor_key),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Attestor, AttestorRemoved, Config, Role};
+
+#[derive(Accounts)]
+pub struct RemoveAttestor<'info> {
+    /// Config admin
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = config_admin,
+        seeds = [b"attestor", attestor.authority.as_ref()],
+        bump = attestor.bump,
+    )]
+    pub attestor: Account<'info, Attestor>,
+}
+
+pub fn handle_remove_attestor(ctx: Context<RemoveAttestor>) -> Result<()> {
+    // The attestor set changes only as openly as the arbiter role does. Once a config
+    // delay is set, it has to be lowered through the queue before this can run
+    require!(ctx.accounts.config.config_delay == 0, EscrowError::TimelockRequired);
+
+    let authority = ctx.accounts.attestor.authority;
+
+    emit!(AttestorRemoved {
+        attestor: authority,
+    });
+
+    msg!("Attestor removed: {}", authority);
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, Denylisted, Role, WalletUndenylisted};
+
+#[derive(Accounts)]
+pub struct RemoveFromDenylist<'info> {
+    /// Config admin
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = config_admin,
+        seeds = [b"denylist", denylisted.wallet.as_ref()],
+        bump = denylisted.bump,
+    )]
+    pub denylisted: Account<'info, Denylisted>,
+}
+
+pub fn handle_remove_from_denylist(ctx: Context<RemoveFromDenylist>) -> Result<()> {
+    let wallet = ctx.accounts.denylisted.wallet;
+
+    emit!(WalletUndenylisted {
+        wallet,
+        removed_by: ctx.accounts.config_admin.key(),
+    });
+
+    msg!("Wallet removed from denylist: {}", wallet);
+    Ok(())
+}
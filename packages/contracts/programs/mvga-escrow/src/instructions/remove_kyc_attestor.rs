@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, KycAttestor, KycAttestorRemoved, Role};
+
+#[derive(Accounts)]
+pub struct RemoveKycAttestor<'info> {
+    /// Config admin
+    #[account(mut)]
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = config_admin,
+        seeds = [b"kyc_attestor", kyc_attestor.authority.as_ref()],
+        bump = kyc_attestor.bump,
+    )]
+    pub kyc_attestor: Account<'info, KycAttestor>,
+}
+
+pub fn handle_remove_kyc_attestor(ctx: Context<RemoveKycAttestor>) -> Result<()> {
+    // Removing a KYC attestor voids every attestation it issued. Once a config
+    // delay is set, it has to be lowered through the queue before this can run
+    require!(ctx.accounts.config.config_delay == 0, EscrowError::TimelockRequired);
+
+    let authority = ctx.accounts.kyc_attestor.authority;
+
+    emit!(KycAttestorRemoved {
+        attestor: authority,
+    });
+
+    msg!("KYC attestor removed: {}", authority);
+    Ok(())
+}
@@ -6,29 +6,42 @@ use anchor_spl::token_interface::{
 };
 
 use crate::errors::EscrowError;
-use crate::state::{DisputeResolved, EscrowState, EscrowStatus};
-use crate::AUTHORIZED_ADMIN;
+use crate::state::{
+    Config, DisputeResolved, EscrowState, EscrowStatus, GlobalStats, ReferrerStats, Role,
+};
+
+use super::release::{pay_beneficiary, FeeAccounts, ReferrerAccounts};
 
-/// Resolution: 0 = release to buyer, 1 = refund to seller
+/// Resolution: 0 = release to beneficiary, 1 = refund to depositor
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Resolution {
-    ReleaseToBuyer,
-    RefundToSeller,
+    ReleaseToBeneficiary,
+    RefundToDepositor,
 }
 
 #[derive(Accounts)]
 pub struct ResolveDispute<'info> {
-    /// Admin resolving the dispute
+    /// Arbiter resolving the dispute
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub arbiter: Signer<'info>,
 
-    /// CHECK: Seller receives rent refund from vault close
-    #[account(mut)]
-    pub seller: UncheckedAccount<'info>,
+    #[account(
+        constraint = config.has_role(Role::Arbiter, &arbiter.key()) @ EscrowError::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
 
-    /// CHECK: Buyer may receive tokens if resolution is ReleaseToBuyer
+    /// CHECK: Whoever paid the escrow's rent gets it back from the vault and state close
+    #[account(
+        mut,
+        constraint = rent_payer.key() == escrow_state.rent_payer @ EscrowError::InvalidRentPayer,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary may receive tokens if resolution is ReleaseToBeneficiary
     #[account(mut)]
-    pub buyer: UncheckedAccount<'info>,
+    pub beneficiary: UncheckedAccount<'info>,
 
     #[account(
         constraint = mint.key() == escrow_state.mint @ EscrowError::InvalidMint,
@@ -37,15 +50,21 @@ pub struct ResolveDispute<'info> {
 
     #[account(
         mut,
-        close = seller,
-        constraint = escrow_state.admin == admin.key() @ EscrowError::UnauthorizedAdmin,
-        constraint = escrow_state.seller == seller.key() @ EscrowError::UnauthorizedSeller,
-        constraint = escrow_state.buyer == buyer.key() @ EscrowError::UnauthorizedBuyer,
-        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.seller.as_ref()],
+        close = rent_payer,
+        constraint = escrow_state.beneficiary == beneficiary.key() @ EscrowError::UnauthorizedBeneficiary,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
         bump = escrow_state.bump,
     )]
     pub escrow_state: Account<'info, EscrowState>,
 
+    /// Statistics for the escrow's mint
+    #[account(
+        mut,
+        seeds = [b"global_stats", escrow_state.mint.as_ref()],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
     /// Vault holding escrowed tokens
     #[account(
         mut,
@@ -57,32 +76,59 @@ pub struct ResolveDispute<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// Recipient's token account (buyer or seller depending on resolution)
+    /// Recipient's associated token account (beneficiary or depositor depending on
+    /// resolution) — omit when the recipient chose a payout or refund account
     #[account(
         init_if_needed,
-        payer = admin,
+        payer = arbiter,
         associated_token::mint = mint,
         associated_token::authority = recipient,
         associated_token::token_program = token_program,
     )]
-    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    /// CHECK: The recipient — buyer (ReleaseToBuyer) or seller (RefundToSeller).
+    /// Payout or refund account the recipient chose for this escrow
+    #[account(mut)]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The recipient — beneficiary (ReleaseToBeneficiary) or depositor (RefundToDepositor).
     /// Validated in handler logic.
     pub recipient: UncheckedAccount<'info>,
 
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    /// Fee recipient's token account — required when a release carries a platform fee
+    #[account(mut)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's referrer — required on a release when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", depositor_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = depositor_referrer.bump,
+    )]
+    pub depositor_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the depositor's referrer is paid its fee share into
+    #[account(mut)]
+    pub depositor_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Beneficiary's referrer — required on a release when the escrow names one
+    #[account(
+        mut,
+        seeds = [b"referrer", beneficiary_referrer.referrer.as_ref(), escrow_state.mint.as_ref()],
+        bump = beneficiary_referrer.bump,
+    )]
+    pub beneficiary_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// Token account the beneficiary's referrer is paid its fee share into
+    #[account(mut)]
+    pub beneficiary_referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
 pub fn handle_resolve(ctx: Context<ResolveDispute>, resolution: Resolution) -> Result<()> {
-    // Redundant admin check — verifies against hardcoded constant, not just stored state
-    require!(
-        ctx.accounts.admin.key() == AUTHORIZED_ADMIN,
-        EscrowError::UnauthorizedAdmin
-    );
-
     let escrow = &ctx.accounts.escrow_state;
 
     // Admin can only resolve disputed escrows
@@ -91,50 +137,85 @@ pub fn handle_resolve(ctx: Context<ResolveDispute>, resolution: Resolution) -> R
         EscrowError::InvalidStatus
     );
 
-    // Validate recipient matches resolution
-    match resolution {
-        Resolution::ReleaseToBuyer => {
+    // Validate recipient matches resolution and pay into the destination it chose
+    let ata = ctx.accounts.recipient_token_account.as_ref();
+    let chosen = ctx.accounts.destination_token_account.as_ref();
+    let destination = match resolution {
+        Resolution::ReleaseToBeneficiary => {
             require!(
-                ctx.accounts.recipient.key() == escrow.buyer,
-                EscrowError::UnauthorizedBuyer
+                ctx.accounts.recipient.key() == escrow.beneficiary,
+                EscrowError::UnauthorizedBeneficiary
             );
+            escrow.payout_destination(ata, chosen)?
         }
-        Resolution::RefundToSeller => {
+        Resolution::RefundToDepositor => {
             require!(
-                ctx.accounts.recipient.key() == escrow.seller,
-                EscrowError::UnauthorizedSeller
+                ctx.accounts.recipient.key() == escrow.depositor,
+                EscrowError::UnauthorizedDepositor
             );
+            escrow.refund_destination(ata, chosen)?
         }
-    }
+    };
 
+    // Outstanding balance only — milestones already released are final
     let amount = escrow.amount;
     let decimals = ctx.accounts.mint.decimals;
 
     let trade_id = escrow.trade_id;
-    let seller_key = escrow.seller;
+    let depositor_key = escrow.depositor;
     let bump = escrow.bump;
     let signer_seeds: &[&[&[u8]]] = &[&[
         b"escrow",
         trade_id.as_ref(),
-        seller_key.as_ref(),
+        depositor_key.as_ref(),
         &[bump],
     ]];
 
-    // Transfer tokens from vault to recipient
-    let cpi_accounts = TransferChecked {
-        from: ctx.accounts.vault.to_account_info(),
-        to: ctx.accounts.recipient_token_account.to_account_info(),
-        authority: ctx.accounts.escrow_state.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
-        .with_signer(signer_seeds);
-    transfer_checked(cpi_ctx, amount, decimals)?;
+    match resolution {
+        // A release ruled by the arbiter pays the platform fee like any other
+        Resolution::ReleaseToBeneficiary => pay_beneficiary(
+            &ctx.accounts.escrow_state,
+            &mut ctx.accounts.global_stats,
+            FeeAccounts {
+                config: &ctx.accounts.config,
+                fee_token_account: ctx.accounts.fee_token_account.as_ref(),
+                referrers: [
+                    ReferrerAccounts {
+                        stats: ctx.accounts.depositor_referrer.as_mut(),
+                        token_account: ctx.accounts.depositor_referrer_token_account.as_ref(),
+                    },
+                    ReferrerAccounts {
+                        stats: ctx.accounts.beneficiary_referrer.as_mut(),
+                        token_account: ctx.accounts.beneficiary_referrer_token_account.as_ref(),
+                    },
+                ],
+            },
+            &ctx.accounts.vault,
+            destination,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            amount,
+        )?,
+        Resolution::RefundToDepositor => {
+            // Transfer tokens from vault to the depositor
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vault.to_account_info(),
+                to: destination.to_account_info(),
+                authority: ctx.accounts.escrow_state.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+            };
+            let cpi_ctx =
+                CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+                    .with_signer(signer_seeds);
+            transfer_checked(cpi_ctx, amount, decimals)?;
+            ctx.accounts.global_stats.returned(amount);
+        }
+    }
 
-    // Close vault, return rent to seller
+    // Close vault, return rent to the rent payer
     let close_accounts = CloseAccount {
         account: ctx.accounts.vault.to_account_info(),
-        destination: ctx.accounts.seller.to_account_info(),
+        destination: ctx.accounts.rent_payer.to_account_info(),
         authority: ctx.accounts.escrow_state.to_account_info(),
     };
     let close_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts)
@@ -144,13 +225,15 @@ pub fn handle_resolve(ctx: Context<ResolveDispute>, resolution: Resolution) -> R
     // Update status
     let escrow = &mut ctx.accounts.escrow_state;
     escrow.status = match resolution {
-        Resolution::ReleaseToBuyer => EscrowStatus::Released,
-        Resolution::RefundToSeller => EscrowStatus::Refunded,
+        Resolution::ReleaseToBeneficiary => EscrowStatus::Released,
+        Resolution::RefundToDepositor => EscrowStatus::Refunded,
     };
 
+    ctx.accounts.global_stats.moved(EscrowStatus::Disputed, escrow.status);
+
     emit!(DisputeResolved {
         trade_id: escrow.trade_id,
-        admin: ctx.accounts.admin.key(),
+        admin: ctx.accounts.arbiter.key(),
         resolution: resolution as u8,
     });
 
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{KycAttestation, KycAttestationRevoked, KycAttestor};
+
+#[derive(Accounts)]
+pub struct RevokeKycAttestation<'info> {
+    /// Any registered KYC attestor — receives the attestation's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"kyc_attestor", authority.key().as_ref()],
+        bump = kyc_attestor.bump,
+    )]
+    pub kyc_attestor: Account<'info, KycAttestor>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"kyc", kyc_attestation.wallet.as_ref()],
+        bump = kyc_attestation.bump,
+    )]
+    pub kyc_attestation: Account<'info, KycAttestation>,
+}
+
+pub fn handle_revoke_kyc_attestation(ctx: Context<RevokeKycAttestation>) -> Result<()> {
+    let wallet = ctx.accounts.kyc_attestation.wallet;
+
+    emit!(KycAttestationRevoked {
+        wallet,
+        attestor: ctx.accounts.authority.key(),
+    });
+
+    msg!("KYC attestation revoked for {}", wallet);
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, Role, RoleRevoked};
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    /// Current config admin
+    pub config_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.has_role(Role::ConfigAdmin, &config_admin.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handle_revoke_role(ctx: Context<RevokeRole>, role: Role) -> Result<()> {
+    // The config admin can only be handed over with grant_role, never left empty
+    require!(role != Role::ConfigAdmin, EscrowError::ConfigAdminNotRevocable);
+    require!(
+        !ctx.accounts.config.requires_timelock(role),
+        EscrowError::TimelockRequired
+    );
+
+    let holder = ctx
+        .accounts
+        .config
+        .set_holder(role, None)
+        .ok_or(EscrowError::RoleNotAssigned)?;
+
+    emit!(RoleRevoked {
+        role,
+        holder,
+        revoked_by: ctx.accounts.config_admin.key(),
+    });
+
+    msg!("Role {:?} revoked from {}", role, holder);
+    Ok(())
+}
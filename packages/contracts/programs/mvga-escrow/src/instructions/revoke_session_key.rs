@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{SessionKey, SessionKeyRevoked};
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    /// Wallet that created the session — rent returns here
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        constraint = session_key.owner == owner.key() @ EscrowError::InvalidSessionKey,
+        seeds = [b"session", session_key.owner.as_ref(), session_key.delegate.as_ref()],
+        bump = session_key.bump,
+    )]
+    pub session_key: Account<'info, SessionKey>,
+}
+
+pub fn handle_revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+    let session = &ctx.accounts.session_key;
+
+    emit!(SessionKeyRevoked {
+        owner: session.owner,
+        delegate: session.delegate,
+        spent: session.spent,
+    });
+
+    msg!("Session key revoked for delegate {}", session.delegate);
+    Ok(())
+}
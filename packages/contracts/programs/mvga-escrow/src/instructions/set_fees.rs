@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, ConfigChange, FeesSet, Role};
+
+#[derive(Accounts)]
+pub struct SetFees<'info> {
+    /// Fee manager
+    pub fee_manager: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.has_role(Role::FeeManager, &fee_manager.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handle_set_fees(
+    ctx: Context<SetFees>,
+    fee_bps: u16,
+    referral_share_bps: u16,
+    fee_recipient: Pubkey,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    // Once a delay is configured, fee changes are proposed through the queue like any other
+    require!(config.config_delay == 0, EscrowError::TimelockRequired);
+
+    config.apply(&ConfigChange::SetFees {
+        fee_bps,
+        referral_share_bps,
+        fee_recipient,
+    })?;
+
+    emit!(FeesSet {
+        fee_bps,
+        referral_share_bps,
+        fee_recipient,
+        fee_manager: ctx.accounts.fee_manager.key(),
+    });
+
+    msg!("Platform fee set to {} bps", fee_bps);
+    Ok(())
+}
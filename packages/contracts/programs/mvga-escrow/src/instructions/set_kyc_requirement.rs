@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::errors::EscrowError;
+use crate::state::{Config, KycRequirementSet, MintPolicy, Role};
+
+#[derive(Accounts)]
+pub struct SetKycRequirement<'info> {
+    /// Mint manager — pays for the mint policy the first time
+    #[account(mut)]
+    pub mint_manager: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::MintManager, &mint_manager.key()) @ EscrowError::MissingRole,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = mint_manager,
+        space = 8 + MintPolicy::INIT_SPACE,
+        seeds = [b"mint_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: Account<'info, MintPolicy>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_set_kyc_requirement(
+    ctx: Context<SetKycRequirement>,
+    kyc_threshold: Option<u64>,
+    kyc_level: u8,
+) -> Result<()> {
+    // Mint policy changes get the same warning period as role changes. Once a config
+    // delay is set, it has to be lowered through the queue before this can run
+    require!(ctx.accounts.config.config_delay == 0, EscrowError::TimelockRequired);
+
+    let policy = &mut ctx.accounts.mint_policy;
+    policy.mint = ctx.accounts.mint.key();
+    policy.kyc_threshold = kyc_threshold;
+    policy.kyc_level = kyc_level;
+    policy.bump = ctx.bumps.mint_policy;
+
+    emit!(KycRequirementSet {
+        mint: policy.mint,
+        kyc_threshold,
+        kyc_level,
+        mint_manager: ctx.accounts.mint_manager.key(),
+    });
+
+    msg!("KYC requirement set for {}", policy.mint);
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::errors::EscrowError;
+use crate::state::{EscrowState, EscrowStatus, RefundAccountSet};
+
+#[derive(Accounts)]
+pub struct SetRefundAccount<'info> {
+    /// Depositor choosing where refunds land
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = escrow_state.depositor == depositor.key() @ EscrowError::UnauthorizedDepositor,
+        constraint = escrow_state.status != EscrowStatus::Released @ EscrowError::InvalidStatus,
+        constraint = escrow_state.status != EscrowStatus::Refunded @ EscrowError::InvalidStatus,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Token account refunds should be paid into
+    pub refund_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+pub fn handle_set_refund_account(ctx: Context<SetRefundAccount>) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow_state;
+    let account = ctx.accounts.refund_token_account.key();
+    escrow.check_destination(&ctx.accounts.refund_token_account)?;
+    escrow.refund_account = Some(account);
+
+    emit!(RefundAccountSet {
+        trade_id: escrow.trade_id,
+        depositor: escrow.depositor,
+        account,
+    });
+
+    msg!("Refund account set to {}", account);
+    Ok(())
+}
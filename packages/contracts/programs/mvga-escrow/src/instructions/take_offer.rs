@@ -0,0 +1,329 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{
+    Config, EscrowDirection, EscrowInitialized, EscrowMode, EscrowState, EscrowStatus, GlobalStats,
+    KycAttestation, KycAttestor, MintPolicy, Offer, OfferFilled, PayoutAccountSet, ReferrerStats,
+    VolumeTracker, ESCROW_VERSION,
+};
+
+use super::initialize::{record_volume, PartyVolume};
+
+#[derive(Accounts)]
+#[instruction(trade_id: [u8; 16])]
+pub struct TakeOffer<'info> {
+    /// Buyer taking a fill — pays rent for the child escrow
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        constraint = mint.key() == offer.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — no new escrows while paused
+    #[account(
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", offer.offer_id.as_ref(), offer.seller.as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Offer vault the fill is reserved from
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = offer,
+        token::token_program = token_program,
+        seeds = [b"vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub offer_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Child escrow — same seeds as a direct escrow with the seller as depositor
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + EscrowState::INIT_SPACE,
+        seeds = [b"escrow", trade_id.as_ref(), offer.seller.as_ref()],
+        bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Child escrow vault owned by the escrow PDA
+    #[account(
+        init,
+        payer = buyer,
+        token::mint = mint,
+        token::authority = escrow_state,
+        token::token_program = token_program,
+        seeds = [b"vault", escrow_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Per-mint statistics, created with the mint's first escrow
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// CHECK: Seller's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", offer.seller.as_ref()],
+        bump,
+        constraint = seller_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub seller_denylist: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", buyer.key().as_ref()],
+        bump,
+        constraint = buyer_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub buyer_denylist: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// Token account the buyer wants to be paid into instead of their ATA — omit for the ATA
+    pub payout_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Registered referrer of the buyer — omit when they have none
+    #[account(
+        mut,
+        seeds = [b"referrer", buyer_referrer.referrer.as_ref(), mint.key().as_ref()],
+        bump = buyer_referrer.bump,
+    )]
+    pub buyer_referrer: Option<Account<'info, ReferrerStats>>,
+
+    /// CHECK: The mint's policy PDA — may not exist, in which case the mint is ungated
+    #[account(
+        seeds = [b"mint_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    /// Seller's KYC attestation — required when the fill is above the mint's threshold
+    #[account(
+        seeds = [b"kyc", offer.seller.as_ref()],
+        bump = seller_kyc.bump,
+    )]
+    pub seller_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `seller_kyc`
+    #[account(
+        seeds = [b"kyc_attestor", seller_kyc_attestor.authority.as_ref()],
+        bump = seller_kyc_attestor.bump,
+    )]
+    pub seller_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Buyer's KYC attestation — required when the fill is above the mint's threshold
+    #[account(
+        seeds = [b"kyc", buyer.key().as_ref()],
+        bump = buyer_kyc.bump,
+    )]
+    pub buyer_kyc: Option<Account<'info, KycAttestation>>,
+
+    /// Registry entry of the attestor behind `buyer_kyc`
+    #[account(
+        seeds = [b"kyc_attestor", buyer_kyc_attestor.authority.as_ref()],
+        bump = buyer_kyc_attestor.bump,
+    )]
+    pub buyer_kyc_attestor: Option<Account<'info, KycAttestor>>,
+
+    /// Seller's rolling volume in this mint — required while the mint caps their volume,
+    /// omit otherwise
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", offer.seller.as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub seller_volume: Option<Box<Account<'info, VolumeTracker>>>,
+
+    /// Buyer's rolling volume in this mint — required while the mint caps their volume,
+    /// omit otherwise
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VolumeTracker::INIT_SPACE,
+        seeds = [b"volume", buyer.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub buyer_volume: Option<Box<Account<'info, VolumeTracker>>>,
+}
+
+pub fn handle_take_offer(
+    ctx: Context<TakeOffer>,
+    trade_id: [u8; 16],
+    amount: u64,
+    payment_method: u8,
+) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+
+    require!(
+        amount >= offer.min_fill && amount <= offer.max_fill,
+        EscrowError::FillOutOfRange
+    );
+    require!(amount <= offer.available, EscrowError::InsufficientLiquidity);
+    require!(
+        payment_method < 32 && offer.payment_methods & (1 << payment_method) != 0,
+        EscrowError::PaymentMethodNotAllowed
+    );
+    require!(
+        ctx.accounts.buyer.key() != offer.seller,
+        EscrowError::BeneficiaryCannotBeDepositor
+    );
+
+    let clock = Clock::get()?;
+
+    // Large fills on a gated mint need both parties verified, as for a direct escrow
+    let policy = MintPolicy::load(&ctx.accounts.mint_policy)?;
+    if let Some(policy) = &policy {
+        let parties = [
+            (ctx.accounts.seller_kyc.as_deref(), ctx.accounts.seller_kyc_attestor.as_deref()),
+            (ctx.accounts.buyer_kyc.as_deref(), ctx.accounts.buyer_kyc_attestor.as_deref()),
+        ];
+        policy.check_kyc(amount, parties, clock.unix_timestamp)?;
+    }
+
+    // The fill counts against both parties' rolling volume, as for a direct escrow
+    let parties = [
+        PartyVolume {
+            wallet: offer.seller,
+            tracker: ctx.accounts.seller_volume.as_deref_mut(),
+            bump: ctx.bumps.seller_volume,
+            kyc: ctx.accounts.seller_kyc.as_deref(),
+            attestor: ctx.accounts.seller_kyc_attestor.as_deref(),
+        },
+        PartyVolume {
+            wallet: ctx.accounts.buyer.key(),
+            tracker: ctx.accounts.buyer_volume.as_deref_mut(),
+            bump: ctx.bumps.buyer_volume,
+            kyc: ctx.accounts.buyer_kyc.as_deref(),
+            attestor: ctx.accounts.buyer_kyc_attestor.as_deref(),
+        },
+    ];
+    record_volume(
+        policy.as_ref(),
+        ctx.accounts.mint.key(),
+        parties,
+        amount,
+        clock.unix_timestamp,
+    )?;
+
+    let offer_id = offer.offer_id;
+    let seller_key = offer.seller;
+    let bump = offer.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"offer",
+        offer_id.as_ref(),
+        seller_key.as_ref(),
+        &[bump],
+    ]];
+
+    // Initialize child escrow — the seller stays the depositor so the regular
+    // mark_paid / release / refund / dispute flow applies unchanged
+    let escrow = &mut ctx.accounts.escrow_state;
+    escrow.trade_id = trade_id;
+    escrow.depositor = seller_key;
+    escrow.beneficiary = ctx.accounts.buyer.key();
+    escrow.mint = ctx.accounts.mint.key();
+    escrow.amount = amount;
+    escrow.status = EscrowStatus::Locked;
+    escrow.admin = offer.admin;
+    escrow.locked_at = clock.unix_timestamp;
+    escrow.created_at = clock.unix_timestamp;
+    escrow.timeout_seconds = offer.timeout_seconds;
+    escrow.bump = ctx.bumps.escrow_state;
+    escrow.direction = EscrowDirection::SellerFunded;
+    escrow.mode = EscrowMode::Standard;
+    escrow.milestones = Vec::new();
+    escrow.next_milestone = 0;
+    escrow.attested_release = false;
+    escrow.rent_payer = ctx.accounts.buyer.key();
+    escrow.payout_account = None;
+    escrow.metadata = None;
+    escrow.payment_reference = None;
+    escrow.payment_reference_updated = false;
+    escrow.version = ESCROW_VERSION;
+    // Offers and signed orders carry no seller referral; the buyer names their own referrer
+    escrow.depositor_referrer = None;
+    escrow.beneficiary_referrer = match ctx.accounts.buyer_referrer.as_mut() {
+        Some(referrer) => Some(referrer.refer([escrow.depositor, escrow.beneficiary])?),
+        None => None,
+    };
+    escrow.refund_account = None;
+    if let Some(payout) = ctx.accounts.payout_token_account.as_ref() {
+        escrow.check_destination(payout)?;
+        escrow.payout_account = Some(payout.key());
+
+        emit!(PayoutAccountSet {
+            trade_id,
+            beneficiary: escrow.beneficiary,
+            account: payout.key(),
+        });
+    }
+
+    // Move the fill from the offer vault into the child vault
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.offer_vault.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.offer.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+        .with_signer(signer_seeds);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+    let offer = &mut ctx.accounts.offer;
+    offer.available -= amount;
+    offer.fill_count += 1;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.opened_from_offer();
+
+    emit!(EscrowInitialized {
+        trade_id,
+        depositor: seller_key,
+        beneficiary: ctx.accounts.buyer.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+        timeout_seconds: offer.timeout_seconds,
+        direction: EscrowDirection::SellerFunded,
+        milestone_count: 0,
+        rent_payer: ctx.accounts.buyer.key(),
+        metadata: None,
+    });
+
+    emit!(OfferFilled {
+        offer_id,
+        trade_id,
+        buyer: ctx.accounts.buyer.key(),
+        amount,
+        payment_method,
+        available: offer.available,
+    });
+
+    msg!("Offer filled: {} tokens locked in child escrow", amount);
+    Ok(())
+}
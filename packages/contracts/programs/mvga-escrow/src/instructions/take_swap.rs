@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+    CloseAccount, close_account,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{Config, GlobalStats, SwapState, SwapTaken};
+
+#[derive(Accounts)]
+pub struct TakeSwap<'info> {
+    /// Taker who pays token B and receives token A
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// CHECK: Maker receives token B and the rent from the closed accounts
+    #[account(
+        mut,
+        constraint = maker.key() == swap_state.maker @ EscrowError::UnauthorizedMaker,
+    )]
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = mint_a.key() == swap_state.mint_a @ EscrowError::InvalidMint,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        constraint = mint_b.key() == swap_state.mint_b @ EscrowError::InvalidMint,
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    /// Program config — swaps can't be taken while paused
+    #[account(
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = maker,
+        constraint = swap_state.taker.is_none() || swap_state.taker == Some(taker.key()) @ EscrowError::UnauthorizedTaker,
+        seeds = [b"swap", swap_state.swap_id.as_ref(), swap_state.maker.as_ref()],
+        bump = swap_state.bump,
+    )]
+    pub swap_state: Account<'info, SwapState>,
+
+    /// Vault holding the maker's token A
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = swap_state,
+        token::token_program = token_program_a,
+        seeds = [b"vault", swap_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Taker's token A account to receive the maker's leg
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub taker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// Taker's token B account to pay from
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = taker,
+    )]
+    pub taker_token_account_b: InterfaceAccount<'info, TokenAccount>,
+
+    /// Maker's token B account to receive the taker's leg
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub maker_token_account_b: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Maker's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", swap_state.maker.as_ref()],
+        bump,
+        constraint = maker_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub maker_denylist: UncheckedAccount<'info>,
+
+    /// CHECK: Taker's denylist PDA — must not exist
+    #[account(
+        seeds = [b"denylist", taker.key().as_ref()],
+        bump,
+        constraint = taker_denylist.data_is_empty() @ EscrowError::WalletDenylisted,
+    )]
+    pub taker_denylist: UncheckedAccount<'info>,
+
+    /// Per-mint statistics — created if missing, since older swaps weren't counted in them
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint_a.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_take_swap(ctx: Context<TakeSwap>) -> Result<()> {
+    let swap = &ctx.accounts.swap_state;
+    let amount_a = swap.amount_a;
+    let amount_b = swap.amount_b;
+
+    let swap_id = swap.swap_id;
+    let maker_key = swap.maker;
+    let bump = swap.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"swap",
+        swap_id.as_ref(),
+        maker_key.as_ref(),
+        &[bump],
+    ]];
+
+    // Leg B: taker pays the maker directly
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.taker_token_account_b.to_account_info(),
+        to: ctx.accounts.maker_token_account_b.to_account_info(),
+        authority: ctx.accounts.taker.to_account_info(),
+        mint: ctx.accounts.mint_b.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program_b.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_ctx, amount_b, ctx.accounts.mint_b.decimals)?;
+
+    // Leg A: vault pays the taker
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.taker_token_account_a.to_account_info(),
+        authority: ctx.accounts.swap_state.to_account_info(),
+        mint: ctx.accounts.mint_a.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program_a.to_account_info(), cpi_accounts)
+        .with_signer(signer_seeds);
+    transfer_checked(cpi_ctx, amount_a, ctx.accounts.mint_a.decimals)?;
+
+    // Close vault account, return rent to maker
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.maker.to_account_info(),
+        authority: ctx.accounts.swap_state.to_account_info(),
+    };
+    let close_ctx = CpiContext::new(ctx.accounts.token_program_a.to_account_info(), close_accounts)
+        .with_signer(signer_seeds);
+    close_account(close_ctx)?;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint_a.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.paid_out(amount_a);
+
+    emit!(SwapTaken {
+        swap_id,
+        maker: maker_key,
+        taker: ctx.accounts.taker.key(),
+        amount_a,
+        amount_b,
+    });
+
+    msg!("Swap settled: {} for {}", amount_a, amount_b);
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{Config, GlobalStats, MintPolicy, Offer, OfferLiquidityChanged};
+
+#[derive(Accounts)]
+pub struct TopUpOffer<'info> {
+    /// Seller adding liquidity to their offer — pays for the mint's statistics if needed
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        constraint = mint.key() == offer.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Program config — no new liquidity while paused
+    #[account(
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = offer.seller == seller.key() @ EscrowError::UnauthorizedDepositor,
+        seeds = [b"offer", offer.offer_id.as_ref(), offer.seller.as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Offer vault receiving the liquidity
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = offer,
+        token::token_program = token_program,
+        seeds = [b"vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub offer_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Seller's token account to transfer from
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Per-mint statistics — the added liquidity counts towards the TVL cap
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// CHECK: The mint's policy PDA — may not exist, in which case the mint is uncapped
+    #[account(
+        seeds = [b"mint_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_top_up_offer(ctx: Context<TopUpOffer>, amount: u64) -> Result<()> {
+    require!(amount > 0, EscrowError::ZeroAmount);
+
+    // Transfer tokens from seller to offer vault
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.seller_token_account.to_account_info(),
+        to: ctx.accounts.offer_vault.to_account_info(),
+        authority: ctx.accounts.seller.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+    let policy = MintPolicy::load(&ctx.accounts.mint_policy)?;
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.deposited(amount, policy.and_then(|policy| policy.tvl_cap))?;
+
+    let offer = &mut ctx.accounts.offer;
+    offer.available = offer
+        .available
+        .checked_add(amount)
+        .ok_or(EscrowError::InsufficientLiquidity)?;
+
+    emit!(OfferLiquidityChanged {
+        offer_id: offer.offer_id,
+        deposited: amount,
+        withdrawn: 0,
+        available: offer.available,
+    });
+
+    msg!("Offer topped up: {} tokens available", offer.available);
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{
+    EscrowState, EscrowStatus, PaymentReference, PaymentReferenceUpdated, SessionKey,
+    SESSION_MARK_PAID,
+};
+
+#[derive(Accounts)]
+pub struct UpdatePaymentReference<'info> {
+    /// Beneficiary correcting their payment proof, or a session delegate acting for them
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = escrow_state.status == EscrowStatus::PaymentSent @ EscrowError::InvalidStatus,
+        constraint = !escrow_state.payment_reference_updated @ EscrowError::PaymentReferenceLocked,
+        seeds = [b"escrow", escrow_state.trade_id.as_ref(), escrow_state.depositor.as_ref()],
+        bump = escrow_state.bump,
+    )]
+    pub escrow_state: Account<'info, EscrowState>,
+
+    /// Beneficiary's session key — omit when the beneficiary signs directly
+    #[account(
+        seeds = [b"session", session_key.owner.as_ref(), session_key.delegate.as_ref()],
+        bump = session_key.bump,
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+}
+
+pub fn handle_update_payment_reference(
+    ctx: Context<UpdatePaymentReference>,
+    payment_reference: PaymentReference,
+) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow_state;
+    SessionKey::authorize(
+        ctx.accounts.session_key.as_deref(),
+        &ctx.accounts.authority.key(),
+        &escrow.beneficiary,
+        SESSION_MARK_PAID,
+        EscrowError::UnauthorizedBeneficiary,
+    )?;
+
+    let previous = escrow.payment_reference;
+    escrow.payment_reference = Some(payment_reference);
+    escrow.payment_reference_updated = true;
+
+    emit!(PaymentReferenceUpdated {
+        trade_id: escrow.trade_id,
+        beneficiary: escrow.beneficiary,
+        previous,
+        payment_reference,
+    });
+
+    msg!("Beneficiary updated the payment reference");
+    Ok(())
+}
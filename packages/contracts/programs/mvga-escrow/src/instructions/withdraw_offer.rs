@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked,
+};
+
+use crate::errors::EscrowError;
+use crate::state::{GlobalStats, Offer, OfferLiquidityChanged};
+
+#[derive(Accounts)]
+pub struct WithdrawOffer<'info> {
+    /// Seller pulling unreserved liquidity out of their offer — pays for the mint's
+    /// statistics if needed
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        constraint = mint.key() == offer.mint @ EscrowError::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = offer.seller == seller.key() @ EscrowError::UnauthorizedDepositor,
+        seeds = [b"offer", offer.offer_id.as_ref(), offer.seller.as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Offer vault holding the unreserved liquidity
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = offer,
+        token::token_program = token_program,
+        seeds = [b"vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub offer_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Seller's token account to receive the withdrawal
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Per-mint statistics — created if missing, since older offers weren't counted in them
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_withdraw_offer(ctx: Context<WithdrawOffer>, amount: u64) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    require!(amount > 0, EscrowError::ZeroAmount);
+    require!(amount <= offer.available, EscrowError::InsufficientLiquidity);
+
+    let offer_id = offer.offer_id;
+    let seller_key = offer.seller;
+    let bump = offer.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"offer",
+        offer_id.as_ref(),
+        seller_key.as_ref(),
+        &[bump],
+    ]];
+
+    // Transfer tokens from offer vault back to seller
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.offer_vault.to_account_info(),
+        to: ctx.accounts.seller_token_account.to_account_info(),
+        authority: ctx.accounts.offer.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+        .with_signer(signer_seeds);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mint = ctx.accounts.mint.key();
+    stats.bump = ctx.bumps.global_stats;
+    stats.returned(amount);
+
+    let offer = &mut ctx.accounts.offer;
+    offer.available -= amount;
+
+    emit!(OfferLiquidityChanged {
+        offer_id,
+        deposited: 0,
+        withdrawn: amount,
+        available: offer.available,
+    });
+
+    msg!("Offer withdrawal: {} tokens returned to seller", amount);
+    Ok(())
+}
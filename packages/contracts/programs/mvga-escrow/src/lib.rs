@@ -2,13 +2,19 @@ use anchor_lang::prelude::*;
 
 pub mod errors;
 pub mod instructions;
+pub mod signature;
 pub mod state;
 
 use instructions::*;
+use state::{
+    ConfigChange, EscrowDirection, EscrowTerms, PaymentAttestation, PaymentReference,
+    ReleaseVoucher, Role, SignedOrder, TradeMetadata, VolumeLimit, VOLUME_LIMIT_TIERS,
+};
 
 declare_id!("6GXdYCDckUVEFBaQSgfQGX95gZSNN7FWN19vRDSyTJ5E");
 
-/// Authorized admin for dispute resolution.
+/// Authorized admin recorded on every escrow and the only key that can create the
+/// program `Config`. Day-to-day privileges live in the roles stored there.
 /// Only this pubkey can be passed as the `admin` account during initialization.
 /// This is the Squads Vault 2 (Admin) address — requires 2-of-3 multisig approval.
 pub const AUTHORIZED_ADMIN: Pubkey = pubkey!("C3KbfmtHrUiWunbsVhpeT1G6MqtyU8kK5mmVaByTUXF8");
@@ -17,38 +23,449 @@ pub const AUTHORIZED_ADMIN: Pubkey = pubkey!("C3KbfmtHrUiWunbsVhpeT1G6MqtyU8kK5m
 pub mod mvga_escrow {
     use super::*;
 
-    /// Seller locks tokens into a PDA-controlled vault.
+    /// Depositor locks tokens into a PDA-controlled vault.
+    /// `direction` records whether the seller or the buyer is funding the trade;
+    /// `terms` selects a single release, an ordered set of milestones, linear vesting
+    /// or a hashlock for cross-chain swaps. `attested_release` lets a registered payment
+    /// attestor release the escrow once the beneficiary has marked it paid. Rent is paid
+    /// by `rent_payer` (optionally reimbursed from the rent sponsor pool) and goes back to
+    /// whoever ended up paying it when the escrow closes. `metadata` optionally records the
+    /// fiat side of the trade (currency, amount, price, payment method, terms hash).
+    /// Either party may be attributed to a registered referrer, passed as its stats account.
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_escrow(
         ctx: Context<InitializeEscrow>,
         trade_id: [u8; 16],
         amount: u64,
         timeout_seconds: u64,
+        direction: EscrowDirection,
+        terms: EscrowTerms,
+        attested_release: bool,
+        metadata: Option<TradeMetadata>,
     ) -> Result<()> {
-        instructions::initialize::handle_initialize(ctx, trade_id, amount, timeout_seconds)
+        instructions::initialize::handle_initialize(
+            ctx,
+            trade_id,
+            amount,
+            timeout_seconds,
+            direction,
+            terms,
+            attested_release,
+            metadata,
+        )
     }
 
-    /// Buyer marks that off-chain payment has been sent.
-    pub fn mark_paid(ctx: Context<MarkPaid>) -> Result<()> {
-        instructions::mark_paid::handle_mark_paid(ctx)
+    /// Beneficiary marks that the off-chain leg (fiat payment or delivery) is done.
+    /// A session delegate holding the mark_paid permission may sign instead, and an
+    /// optional payout token account replaces the beneficiary's ATA on every release path.
+    /// `payment_reference` carries the hashed bank reference and claimed fiat amount.
+    pub fn mark_paid(
+        ctx: Context<MarkPaid>,
+        payment_reference: Option<PaymentReference>,
+    ) -> Result<()> {
+        instructions::mark_paid::handle_mark_paid(ctx, payment_reference)
     }
 
-    /// Seller confirms receipt of off-chain payment → tokens released to buyer.
+    /// Depositor confirms the off-chain leg → tokens released to beneficiary.
+    /// Milestone escrows release only the current milestone and stay open until the last one.
+    /// A session delegate may sign instead, within the session's spend cap. The platform fee
+    /// is taken from the released amount and shared with the escrow's referrers.
     pub fn release_escrow(ctx: Context<ReleaseEscrow>) -> Result<()> {
         instructions::release::handle_release(ctx)
     }
 
-    /// Seller self-refunds after timeout (status must be Locked, timeout elapsed).
+    /// Depositor self-refunds after timeout (status must be Locked, timeout elapsed).
+    /// For milestone escrows the current milestone's deadline applies and only the
+    /// outstanding balance is returned.
     pub fn refund_escrow(ctx: Context<RefundEscrow>) -> Result<()> {
         instructions::refund::handle_refund(ctx)
     }
 
-    /// Either buyer or seller files a dispute.
+    /// Either depositor or beneficiary files a dispute, directly or through a session delegate.
     pub fn file_dispute(ctx: Context<FileDispute>) -> Result<()> {
         instructions::dispute::handle_dispute(ctx)
     }
 
-    /// Admin resolves a dispute — releases to buyer or refunds to seller.
+    /// Arbiter resolves a dispute — releases to beneficiary or refunds to depositor.
+    /// A release pays the platform fee and referral shares as `release_escrow` does.
     pub fn resolve_dispute(ctx: Context<ResolveDispute>, resolution: Resolution) -> Result<()> {
         instructions::resolve::handle_resolve(ctx, resolution)
     }
+
+    /// Beneficiary claims whatever has vested so far on a vesting escrow, less the platform fee.
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        instructions::claim_vested::handle_claim_vested(ctx)
+    }
+
+    /// Depositor stops a vesting escrow — vested tokens go to the beneficiary, less the
+    /// platform fee, and the unvested remainder back to the depositor.
+    pub fn cancel_vesting(ctx: Context<CancelVesting>) -> Result<()> {
+        instructions::cancel_vesting::handle_cancel_vesting(ctx)
+    }
+
+    /// Anyone reveals the hashlock preimage → tokens released to beneficiary, less the
+    /// platform fee.
+    /// After the timeout the depositor refunds through `refund_escrow` instead.
+    pub fn claim_with_preimage(ctx: Context<ClaimWithPreimage>, preimage: Vec<u8>) -> Result<()> {
+        instructions::claim_preimage::handle_claim_with_preimage(ctx, preimage)
+    }
+
+    /// Maker locks token A and names the amount of token B wanted in return.
+    /// `taker` optionally restricts who may take the swap.
+    pub fn initialize_swap(
+        ctx: Context<InitializeSwap>,
+        swap_id: [u8; 16],
+        amount_a: u64,
+        amount_b: u64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::initialize_swap::handle_initialize_swap(ctx, swap_id, amount_a, amount_b, taker)
+    }
+
+    /// Taker pays token B to the maker and receives token A — both legs settle atomically.
+    pub fn take_swap(ctx: Context<TakeSwap>) -> Result<()> {
+        instructions::take_swap::handle_take_swap(ctx)
+    }
+
+    /// Maker cancels a swap nobody has taken yet.
+    pub fn cancel_swap(ctx: Context<CancelSwap>) -> Result<()> {
+        instructions::cancel_swap::handle_cancel_swap(ctx)
+    }
+
+    /// Seller publishes a standing offer and deposits its liquidity up front.
+    /// Buyers fill any amount between `min_fill` and `max_fill` using one of the
+    /// `payment_methods` bits; `price` and `fiat_currency` are informational.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_offer(
+        ctx: Context<CreateOffer>,
+        offer_id: [u8; 16],
+        amount: u64,
+        min_fill: u64,
+        max_fill: u64,
+        payment_methods: u32,
+        price: u64,
+        fiat_currency: [u8; 3],
+        timeout_seconds: u64,
+    ) -> Result<()> {
+        instructions::create_offer::handle_create_offer(
+            ctx,
+            offer_id,
+            amount,
+            min_fill,
+            max_fill,
+            payment_methods,
+            price,
+            fiat_currency,
+            timeout_seconds,
+        )
+    }
+
+    /// Buyer takes a fill from an offer → tokens move into a child escrow that follows
+    /// the regular mark_paid / release / refund / dispute flow. The buyer may name their
+    /// referrer.
+    pub fn take_offer(
+        ctx: Context<TakeOffer>,
+        trade_id: [u8; 16],
+        amount: u64,
+        payment_method: u8,
+    ) -> Result<()> {
+        instructions::take_offer::handle_take_offer(ctx, trade_id, amount, payment_method)
+    }
+
+    /// Seller adds liquidity to an open offer.
+    pub fn top_up_offer(ctx: Context<TopUpOffer>, amount: u64) -> Result<()> {
+        instructions::top_up_offer::handle_top_up_offer(ctx, amount)
+    }
+
+    /// Seller withdraws liquidity that has not been reserved by a fill.
+    pub fn withdraw_offer(ctx: Context<WithdrawOffer>, amount: u64) -> Result<()> {
+        instructions::withdraw_offer::handle_withdraw_offer(ctx, amount)
+    }
+
+    /// Seller closes an offer — remaining liquidity and rent return to the seller.
+    pub fn close_offer(ctx: Context<CloseOffer>) -> Result<()> {
+        instructions::close_offer::handle_close_offer(ctx)
+    }
+
+    /// Buyer settles a seller's off-chain signed order → escrow created in one transaction.
+    /// Must directly follow an ed25519 program instruction with the seller's signature over
+    /// `order.message()`; tokens are pulled through the seller's delegate approval to the
+    /// `order_authority` PDA.
+    pub fn fill_signed_order(
+        ctx: Context<FillSignedOrder>,
+        order: SignedOrder,
+        trade_id: [u8; 16],
+        amount: u64,
+    ) -> Result<()> {
+        instructions::fill_signed_order::handle_fill_signed_order(ctx, order, trade_id, amount)
+    }
+
+    /// Seller revokes a signed order by burning its nonce before anyone fills it.
+    pub fn cancel_signed_order(ctx: Context<CancelSignedOrder>, nonce: u64) -> Result<()> {
+        instructions::cancel_signed_order::handle_cancel_signed_order(ctx, nonce)
+    }
+
+    /// Anyone submits a depositor-signed release voucher → same payout as `release_escrow`
+    /// without the depositor paying fees. Must directly follow an ed25519 program
+    /// instruction with the depositor's signature over `voucher.message()`; rent still
+    /// returns to the depositor.
+    pub fn release_with_voucher(
+        ctx: Context<ReleaseWithVoucher>,
+        voucher: ReleaseVoucher,
+    ) -> Result<()> {
+        instructions::release_voucher::handle_release_with_voucher(ctx, voucher)
+    }
+
+    /// Config admin registers a backend key allowed to attest fiat receipt. Only while no
+    /// config delay is set.
+    pub fn register_attestor(ctx: Context<RegisterAttestor>, authority: Pubkey) -> Result<()> {
+        instructions::register_attestor::handle_register_attestor(ctx, authority)
+    }
+
+    /// Config admin removes a payment attestor. Only while no config delay is set.
+    pub fn remove_attestor(ctx: Context<RemoveAttestor>) -> Result<()> {
+        instructions::remove_attestor::handle_remove_attestor(ctx)
+    }
+
+    /// Anyone submits a registered attestor's payment confirmation → the current release
+    /// step is paid out without the depositor's signature. Only for escrows whose depositor
+    /// opted in at initialization; must directly follow an ed25519 program instruction with
+    /// the attestor's signature over `attestation.message()`.
+    pub fn release_with_attestation(
+        ctx: Context<ReleaseWithAttestation>,
+        attestation: PaymentAttestation,
+    ) -> Result<()> {
+        instructions::release_attested::handle_release_with_attestation(ctx, attestation)
+    }
+
+    /// Trader delegates mark_paid / release / file_dispute to an ephemeral hot key.
+    /// `permissions` is a bitmask of `SESSION_*` flags; delegated releases are capped at
+    /// `spend_cap` in total and the session stops working after `expires_at`.
+    pub fn create_session_key(
+        ctx: Context<CreateSessionKey>,
+        delegate: Pubkey,
+        permissions: u8,
+        expires_at: i64,
+        spend_cap: u64,
+    ) -> Result<()> {
+        instructions::create_session_key::handle_create_session_key(
+            ctx,
+            delegate,
+            permissions,
+            expires_at,
+            spend_cap,
+        )
+    }
+
+    /// Trader revokes a session key before it expires — rent returns to the trader.
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        instructions::revoke_session_key::handle_revoke_session_key(ctx)
+    }
+
+    /// Fee manager creates the rent sponsor pool or changes the operator allowed to draw on it.
+    /// Only while no config delay is set.
+    pub fn configure_rent_sponsor(ctx: Context<ConfigureRentSponsor>, operator: Pubkey) -> Result<()> {
+        instructions::configure_rent_sponsor::handle_configure_rent_sponsor(ctx, operator)
+    }
+
+    /// Treasury tops up the rent sponsor pool out of platform fee revenue.
+    pub fn fund_rent_sponsor(ctx: Context<FundRentSponsor>, lamports: u64) -> Result<()> {
+        instructions::fund_rent_sponsor::handle_fund_rent_sponsor(ctx, lamports)
+    }
+
+    /// Depositor points refunds at a token account other than their own — an exchange
+    /// deposit address, a PDA-owned account or a multisig vault.
+    pub fn set_refund_account(ctx: Context<SetRefundAccount>) -> Result<()> {
+        instructions::set_refund_account::handle_set_refund_account(ctx)
+    }
+
+    /// Both parties agree to change a locked standard escrow's amount — the depositor tops
+    /// up the vault or gets the difference back — and optionally extend its timeout.
+    pub fn amend_escrow(
+        ctx: Context<AmendEscrow>,
+        new_amount: u64,
+        new_timeout_seconds: Option<u64>,
+    ) -> Result<()> {
+        instructions::amend_escrow::handle_amend_escrow(ctx, new_amount, new_timeout_seconds)
+    }
+
+    /// Both parties agree to push back the depositor's self-refund on a standard or milestone
+    /// escrow, e.g. over a bank holiday. The refund deadline stays within 30 days of opening.
+    pub fn extend_timeout(ctx: Context<ExtendTimeout>, new_timeout_seconds: u64) -> Result<()> {
+        instructions::extend_timeout::handle_extend_timeout(ctx, new_timeout_seconds)
+    }
+
+    /// Beneficiary replaces the payment reference submitted with `mark_paid` — once.
+    pub fn update_payment_reference(
+        ctx: Context<UpdatePaymentReference>,
+        payment_reference: PaymentReference,
+    ) -> Result<()> {
+        instructions::update_payment_reference::handle_update_payment_reference(
+            ctx,
+            payment_reference,
+        )
+    }
+
+    /// Upgrades an escrow opened on the launch layout to the current versioned layout,
+    /// growing the account in place. The payer covers the extra rent.
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        instructions::migrate_escrow::handle_migrate_escrow(ctx)
+    }
+
+    /// AUTHORIZED_ADMIN creates the program config and names the config admin, which
+    /// hands out every other role from then on.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, config_admin: Pubkey) -> Result<()> {
+        instructions::initialize_config::handle_initialize_config(ctx, config_admin)
+    }
+
+    /// Config admin assigns a role (config admin, pauser, arbiter, fee manager or mint
+    /// manager) to a key, replacing the previous holder. Once a config delay is set, every
+    /// role but the pauser has to go through `propose_config_change` instead.
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+        instructions::grant_role::handle_grant_role(ctx, role, holder)
+    }
+
+    /// Config admin clears a role. The config admin role itself can only be handed over.
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: Role) -> Result<()> {
+        instructions::revoke_role::handle_revoke_role(ctx, role)
+    }
+
+    /// Pauser (or config admin) stops new escrows, top-ups, offers and swaps from taking in
+    /// funds. Open escrows can still be settled.
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        instructions::pause::handle_pause(ctx)
+    }
+
+    /// Config admin lifts a pause.
+    pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
+        instructions::pause::handle_unpause(ctx)
+    }
+
+    /// Config admin queues a config change (role assignment, the delay itself or the
+    /// emergency grace period). It can be executed once the config delay has passed,
+    /// giving traders warning.
+    pub fn propose_config_change(
+        ctx: Context<ProposeConfigChange>,
+        change: ConfigChange,
+    ) -> Result<()> {
+        instructions::propose_config_change::handle_propose_config_change(ctx, change)
+    }
+
+    /// Config admin applies a queued change whose delay has passed.
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        instructions::execute_config_change::handle_execute_config_change(ctx)
+    }
+
+    /// Config admin drops a queued change before it is executed.
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        instructions::cancel_config_change::handle_cancel_config_change(ctx)
+    }
+
+    /// Anyone closes an open escrow once the program has been paused for longer than the
+    /// emergency grace period. Locked funds go back to the depositor; once payment was
+    /// claimed or disputed they are split 50/50. Rent goes back to whoever paid it.
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
+        instructions::emergency_withdraw::handle_emergency_withdraw(ctx)
+    }
+
+    /// Config admin denylists a wallet: it can no longer open, accept or mark paid escrows.
+    pub fn add_to_denylist(ctx: Context<AddToDenylist>, wallet: Pubkey) -> Result<()> {
+        instructions::add_to_denylist::handle_add_to_denylist(ctx, wallet)
+    }
+
+    /// Config admin lifts a wallet's denylisting.
+    pub fn remove_from_denylist(ctx: Context<RemoveFromDenylist>) -> Result<()> {
+        instructions::remove_from_denylist::handle_remove_from_denylist(ctx)
+    }
+
+    /// Config admin moves an open escrow involving a denylisted wallet to `Disputed`,
+    /// leaving the funds for the arbiter instead of freezing them.
+    pub fn dispute_denylisted_escrow(ctx: Context<DisputeDenylistedEscrow>) -> Result<()> {
+        instructions::dispute_denylisted_escrow::handle_dispute_denylisted_escrow(ctx)
+    }
+
+    /// Config admin registers a backend key allowed to issue KYC attestations. Only while no
+    /// config delay is set.
+    pub fn register_kyc_attestor(ctx: Context<RegisterKycAttestor>, authority: Pubkey) -> Result<()> {
+        instructions::register_kyc_attestor::handle_register_kyc_attestor(ctx, authority)
+    }
+
+    /// Config admin removes a KYC attestor. Only while no config delay is set.
+    pub fn remove_kyc_attestor(ctx: Context<RemoveKycAttestor>) -> Result<()> {
+        instructions::remove_kyc_attestor::handle_remove_kyc_attestor(ctx)
+    }
+
+    /// KYC attestor records (or refreshes) a wallet's verification level and expiry.
+    pub fn issue_kyc_attestation(
+        ctx: Context<IssueKycAttestation>,
+        wallet: Pubkey,
+        level: u8,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::issue_kyc_attestation::handle_issue_kyc_attestation(
+            ctx,
+            wallet,
+            level,
+            expires_at,
+        )
+    }
+
+    /// KYC attestor withdraws a wallet's attestation.
+    pub fn revoke_kyc_attestation(ctx: Context<RevokeKycAttestation>) -> Result<()> {
+        instructions::revoke_kyc_attestation::handle_revoke_kyc_attestation(ctx)
+    }
+
+    /// Mint manager sets the escrow amount above which both parties need a KYC
+    /// attestation of at least `kyc_level`. `None` keeps the mint permissionless. Only while
+    /// no config delay is set.
+    pub fn set_kyc_requirement(
+        ctx: Context<SetKycRequirement>,
+        kyc_threshold: Option<u64>,
+        kyc_level: u8,
+    ) -> Result<()> {
+        instructions::set_kyc_requirement::handle_set_kyc_requirement(ctx, kyc_threshold, kyc_level)
+    }
+
+    /// Mint manager sets the rolling 24h and 30-day volume caps per wallet, one tier per
+    /// KYC level (tier 0 for unverified wallets). Only while no config delay is set.
+    pub fn set_volume_limits(
+        ctx: Context<SetVolumeLimits>,
+        volume_limits: [VolumeLimit; VOLUME_LIMIT_TIERS],
+    ) -> Result<()> {
+        instructions::set_volume_limits::handle_set_volume_limits(ctx, volume_limits)
+    }
+
+    /// Mint manager caps how much may sit in the mint's escrow, offer and swap vaults at
+    /// once. Only while no config delay is set.
+    pub fn set_tvl_cap(ctx: Context<SetTvlCap>, tvl_cap: Option<u64>) -> Result<()> {
+        instructions::set_tvl_cap::handle_set_tvl_cap(ctx, tvl_cap)
+    }
+
+    /// Anyone creates a mint's stats account. Opening an escrow does this automatically;
+    /// this is for mints whose open escrows predate the stats.
+    pub fn initialize_global_stats(ctx: Context<InitializeGlobalStats>) -> Result<()> {
+        instructions::initialize_global_stats::handle_initialize_global_stats(ctx)
+    }
+
+    /// Fee manager sets the platform fee taken on release, each referrer's share of it and
+    /// the wallet collecting the rest. Goes through the config change queue once a delay is set.
+    pub fn set_fees(
+        ctx: Context<SetFees>,
+        fee_bps: u16,
+        referral_share_bps: u16,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        instructions::set_fees::handle_set_fees(
+            ctx,
+            fee_bps,
+            referral_share_bps,
+            fee_recipient,
+        )
+    }
+
+    /// A wallet signs up as a referrer for a mint. Escrows can then name it as a party's
+    /// referrer, and it earns a share of the platform fee when they are released.
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        instructions::register_referrer::handle_register_referrer(ctx)
+    }
 }
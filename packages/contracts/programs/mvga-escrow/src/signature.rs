@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+
+use crate::errors::EscrowError;
+
+/// Size of the `Ed25519SignatureOffsets` struct in an ed25519 program instruction
+const OFFSETS_LEN: usize = 14;
+/// Offsets start after the signature count and a padding byte
+const OFFSETS_START: usize = 2;
+/// Instruction index meaning "this same ed25519 instruction"
+const SAME_INSTRUCTION: u16 = u16::MAX;
+
+/// Verifies that the instruction immediately before the current one is an ed25519
+/// program instruction carrying exactly one signature by `signer` over `message`.
+///
+/// The ed25519 program has already checked the signature itself by the time we run —
+/// a failing signature aborts the whole transaction — so all that's left is to make
+/// sure it covered the key and bytes we expect.
+pub fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, EscrowError::InvalidSignature);
+    let ix = load_instruction_at_checked(current as usize - 1, instructions)?;
+
+    require!(
+        ix.program_id == solana_sdk_ids::ed25519_program::ID && ix.accounts.is_empty(),
+        EscrowError::InvalidSignature
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= OFFSETS_START + OFFSETS_LEN && data[0] == 1,
+        EscrowError::InvalidSignature
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = OFFSETS_START;
+    let signature_ix = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_len = read_u16(offsets + 10) as usize;
+    let message_ix = read_u16(offsets + 12);
+
+    // Signature, key and message must all live inline in the ed25519 instruction
+    require!(
+        signature_ix == SAME_INSTRUCTION
+            && pubkey_ix == SAME_INSTRUCTION
+            && message_ix == SAME_INSTRUCTION,
+        EscrowError::InvalidSignature
+    );
+
+    let signed_key = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(EscrowError::InvalidSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(EscrowError::InvalidSignature)?;
+
+    require!(
+        signed_key == signer.as_ref() && signed_message == message,
+        EscrowError::InvalidSignature
+    );
+
+    Ok(())
+}
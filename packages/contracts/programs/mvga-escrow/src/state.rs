@@ -1,14 +1,78 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::errors::EscrowError;
+
+/// Maximum number of milestones a single escrow can be split into
+pub const MAX_MILESTONES: usize = 8;
+
+/// Maximum preimage length accepted by `claim_with_preimage`
+pub const MAX_PREIMAGE_LEN: usize = 64;
+
+/// Bytes kept free at the end of `EscrowState` for fields added after launch. The tail
+/// follows the variable-length fields, so its offset differs from account to account and
+/// escrows written before a field was carved out hold zeros where it now sits: every field
+/// carved from it must read all-zero bytes as its unset value (`None`, `0`, `false`).
+pub const ESCROW_RESERVED_SPACE: usize = 11;
+
+/// Layout version written into every new or migrated `EscrowState`. Version 0 is the
+/// launch layout (`EscrowStateV0`), which has no version byte.
+pub const ESCROW_VERSION: u8 = 1;
+
+/// Zeroed tail of `Config` kept for settings added later without a realloc
+pub const CONFIG_RESERVED_SPACE: usize = 68;
+
+/// Ceiling on the platform fee, in basis points of the released amount
+pub const MAX_FEE_BPS: u16 = 500;
+
+/// Ceiling on each referrer's share of the platform fee, in basis points of the fee. An escrow
+/// has up to two referrers, so together they never get more than the whole fee.
+pub const MAX_REFERRAL_SHARE_BPS: u16 = 5_000;
+
+/// Zeroed tail of `GlobalStats` kept for counters added later
+pub const GLOBAL_STATS_RESERVED_SPACE: usize = 64;
+
+/// Zeroed tail of `MintPolicy` kept for per-mint settings added later
+pub const MINT_POLICY_RESERVED_SPACE: usize = 47;
+
+/// Volume limit tiers per mint, indexed by KYC level. Wallets without a valid attestation
+/// use tier 0; levels past the last tier use the last one.
+pub const VOLUME_LIMIT_TIERS: usize = 4;
+
+/// Hourly buckets backing the rolling 24h volume
+pub const DAILY_VOLUME_BUCKETS: usize = 24;
+
+/// Daily buckets backing the rolling 30-day volume
+pub const MONTHLY_VOLUME_BUCKETS: usize = 30;
+
+/// Longest delay that can be put on config changes
+pub const MAX_CONFIG_DELAY: i64 = 30 * 24 * 3600;
+
+/// How long the program stays paused before `emergency_withdraw` opens, unless changed
+pub const DEFAULT_EMERGENCY_GRACE_PERIOD: i64 = 14 * 24 * 3600;
+
+/// Longest emergency grace period that can be configured
+pub const MAX_EMERGENCY_GRACE_PERIOD: i64 = 90 * 24 * 3600;
+
+/// Longest a session key may stay valid
+pub const MAX_SESSION_DURATION: i64 = 30 * 24 * 3600;
+
+/// Session key permission: mark an escrow paid on the beneficiary's behalf
+pub const SESSION_MARK_PAID: u8 = 1 << 0;
+/// Session key permission: release an escrow on the depositor's behalf, within the spend cap
+pub const SESSION_RELEASE: u8 = 1 << 1;
+/// Session key permission: file a dispute on either party's behalf
+pub const SESSION_FILE_DISPUTE: u8 = 1 << 2;
 
 #[account]
 #[derive(InitSpace)]
 pub struct EscrowState {
     /// Trade UUID (128-bit, matches DB trade ID)
     pub trade_id: [u8; 16],
-    /// Seller's public key
-    pub seller: Pubkey,
-    /// Buyer's public key
-    pub buyer: Pubkey,
+    /// Party that locks tokens into the vault (seller in a classic P2P trade)
+    pub depositor: Pubkey,
+    /// Party that receives the tokens on release (buyer in a classic P2P trade)
+    pub beneficiary: Pubkey,
     /// Token mint (USDC or MVGA)
     pub mint: Pubkey,
     /// Escrowed token amount (raw, in smallest units)
@@ -19,56 +83,1158 @@ pub struct EscrowState {
     pub admin: Pubkey,
     /// Unix timestamp when escrow was locked
     pub locked_at: i64,
-    /// Seconds after which seller can self-refund (e.g. 7200 = 2 hours)
+    /// Seconds after which depositor can self-refund (e.g. 7200 = 2 hours)
     pub timeout_seconds: u64,
     /// PDA bump seed
     pub bump: u8,
+    /// Which side of the trade funded the escrow
+    pub direction: EscrowDirection,
+    /// Release schedule of the escrow
+    pub mode: EscrowMode,
+    /// Ordered milestones (empty unless `mode` is Milestones)
+    #[max_len(MAX_MILESTONES)]
+    pub milestones: Vec<Milestone>,
+    /// Index of the next milestone awaiting release
+    pub next_milestone: u8,
+    /// Depositor opted in to release by a registered payment attestor
+    pub attested_release: bool,
+    /// Account that paid rent for the state and vault — receives it back on close
+    pub rent_payer: Pubkey,
+    /// Token account the beneficiary chose to be paid into instead of their ATA
+    pub payout_account: Option<Pubkey>,
+    /// Token account the depositor chose to be refunded into instead of their ATA
+    pub refund_account: Option<Pubkey>,
+    /// Off-chain trade details recorded at initialization, if the client supplied them
+    pub metadata: Option<TradeMetadata>,
+    /// Beneficiary's proof of the off-chain payment, submitted with `mark_paid`
+    pub payment_reference: Option<PaymentReference>,
+    /// The beneficiary already used their one update of `payment_reference`
+    pub payment_reference_updated: bool,
+    /// Layout version, see `ESCROW_VERSION`
+    pub version: u8,
+    /// Wallet that referred the depositor — earns a share of the platform fee on release
+    pub depositor_referrer: Option<Pubkey>,
+    /// Wallet that referred the beneficiary
+    pub beneficiary_referrer: Option<Pubkey>,
+    /// Unix timestamp when the escrow was opened — unlike `locked_at` it never moves, and
+    /// vouchers and attestations sign it so they can't outlive the escrow they were made for
+    pub created_at: i64,
+    /// Zeroed padding — new fields are carved out of it instead of reallocating, see
+    /// `ESCROW_RESERVED_SPACE` for what such a field must satisfy
+    pub reserved: [u8; ESCROW_RESERVED_SPACE],
+}
+
+impl EscrowState {
+    /// Unix timestamp after which the depositor may self-refund a Locked escrow.
+    /// The current milestone's deadline takes precedence over the generic timeout.
+    pub fn refund_deadline(&self) -> Result<i64> {
+        if self.mode == EscrowMode::Milestones {
+            if let Some(deadline) = self
+                .milestones
+                .get(self.next_milestone as usize)
+                .and_then(|m| m.deadline)
+            {
+                return Ok(deadline);
+            }
+        }
+        self.locked_at
+            .checked_add(self.timeout_seconds as i64)
+            .ok_or(EscrowError::TimeoutOverflow.into())
+    }
+
+    /// When the escrow was opened. Escrows from before `created_at` was recorded fall back
+    /// to `locked_at`, which only moves on milestone escrows.
+    pub fn opened_at(&self) -> i64 {
+        if self.created_at == 0 {
+            self.locked_at
+        } else {
+            self.created_at
+        }
+    }
+
+    /// Tokens paid out by the next release — the whole balance, or the current milestone.
+    /// Vesting and hashlock escrows pay out through their own instructions.
+    pub fn next_release_amount(&self) -> Result<u64> {
+        match self.mode {
+            EscrowMode::Standard => Ok(self.amount),
+            EscrowMode::Milestones => Ok(self.milestones[self.next_milestone as usize].amount),
+            EscrowMode::Vesting { .. } | EscrowMode::Hashlock { .. } => {
+                Err(EscrowError::InvalidMode.into())
+            }
+        }
+    }
+
+    /// Total amount unlocked for the beneficiary at `now` (vesting escrows only)
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        match self.mode {
+            EscrowMode::Vesting { start, end, total } => {
+                if now <= start {
+                    return Ok(0);
+                }
+                if now >= end {
+                    return Ok(total);
+                }
+                let elapsed = (now - start) as u128;
+                let duration = (end - start) as u128;
+                Ok((total as u128 * elapsed / duration) as u64)
+            }
+            _ => Err(EscrowError::InvalidMode.into()),
+        }
+    }
+
+    /// Checks that a payout or refund destination can receive this escrow's tokens
+    pub fn check_destination(&self, account: &TokenAccount) -> Result<()> {
+        require!(account.mint == self.mint, EscrowError::InvalidMint);
+        require!(!account.is_frozen(), EscrowError::DestinationFrozen);
+        Ok(())
+    }
+
+    /// Token account the beneficiary gets paid into: their chosen payout account if they
+    /// set one, otherwise their associated token account
+    pub fn payout_destination<'a, 'info>(
+        &self,
+        ata: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+        payout: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    ) -> Result<&'a InterfaceAccount<'info, TokenAccount>> {
+        select_destination(self.payout_account, ata, payout)
+    }
+
+    /// Token account the depositor gets refunded into: their chosen refund account if they
+    /// set one, otherwise their own token account
+    pub fn refund_destination<'a, 'info>(
+        &self,
+        owned: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+        refund: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    ) -> Result<&'a InterfaceAccount<'info, TokenAccount>> {
+        select_destination(self.refund_account, owned, refund)
+    }
+
+    /// Amount already claimed by the beneficiary — everything no longer in the vault
+    pub fn vested_claimed(&self) -> Result<u64> {
+        match self.mode {
+            EscrowMode::Vesting { total, .. } => Ok(total.saturating_sub(self.amount)),
+            _ => Err(EscrowError::InvalidMode.into()),
+        }
+    }
+}
+
+fn select_destination<'a, 'info>(
+    chosen: Option<Pubkey>,
+    default: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    custom: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+) -> Result<&'a InterfaceAccount<'info, TokenAccount>> {
+    match chosen {
+        Some(key) => custom
+            .filter(|account| account.key() == key)
+            .ok_or(EscrowError::InvalidDestination.into()),
+        None => default.ok_or(EscrowError::InvalidDestination.into()),
+    }
+}
+
+/// Launch layout of `EscrowState`, still held by escrows opened before versioning.
+/// Shares its discriminator and leading fields with the current layout; upgraded in
+/// place by `migrate_escrow`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct EscrowStateV0 {
+    pub trade_id: [u8; 16],
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub status: EscrowStatus,
+    pub admin: Pubkey,
+    pub locked_at: i64,
+    pub timeout_seconds: u64,
+    pub bump: u8,
+}
+
+impl EscrowStateV0 {
+    /// Current layout of a launch escrow: a seller-funded standard escrow whose seller
+    /// paid the rent
+    pub fn upgrade(self) -> EscrowState {
+        EscrowState {
+            trade_id: self.trade_id,
+            depositor: self.seller,
+            beneficiary: self.buyer,
+            mint: self.mint,
+            amount: self.amount,
+            status: self.status,
+            admin: self.admin,
+            locked_at: self.locked_at,
+            timeout_seconds: self.timeout_seconds,
+            bump: self.bump,
+            direction: EscrowDirection::SellerFunded,
+            mode: EscrowMode::Standard,
+            milestones: Vec::new(),
+            next_milestone: 0,
+            attested_release: false,
+            rent_payer: self.seller,
+            payout_account: None,
+            refund_account: None,
+            metadata: None,
+            payment_reference: None,
+            payment_reference_updated: false,
+            version: ESCROW_VERSION,
+            depositor_referrer: None,
+            beneficiary_referrer: None,
+            created_at: self.locked_at,
+            reserved: [0; ESCROW_RESERVED_SPACE],
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EscrowDirection {
+    /// Seller locks crypto, buyer pays fiat off-chain, seller confirms
+    SellerFunded,
+    /// Buyer locks crypto, seller delivers off-chain, buyer confirms
+    BuyerFunded,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EscrowMode {
+    /// Whole amount released in a single `release_escrow`
+    Standard,
+    /// Amount released milestone by milestone, vault closes after the last one
+    Milestones,
+    /// `total` unlocks linearly between `start` and `end`, claimed via `claim_vested`
+    Vesting { start: i64, end: i64, total: u64 },
+    /// Released to the beneficiary by whoever reveals the preimage of `hash`
+    Hashlock { algorithm: HashAlgorithm, hash: [u8; 32] },
+}
+
+impl EscrowMode {
+    pub fn is_vesting(&self) -> bool {
+        matches!(self, EscrowMode::Vesting { .. })
+    }
+
+    /// Whether the escrow waits on an off-chain leg the beneficiary can mark and dispute
+    pub fn has_offchain_leg(&self) -> bool {
+        matches!(self, EscrowMode::Standard | EscrowMode::Milestones)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum HashAlgorithm {
+    /// Bitcoin / Lightning payment hashes
+    Sha256,
+    /// EVM-compatible `keccak256`
+    Keccak256,
+}
+
+impl HashAlgorithm {
+    pub fn digest(&self, preimage: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => solana_sha256_hasher::hash(preimage).to_bytes(),
+            HashAlgorithm::Keccak256 => solana_keccak_hasher::hash(preimage).to_bytes(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Milestone {
+    /// Tokens released when this milestone is confirmed
+    pub amount: u64,
+    /// Optional unix timestamp after which the depositor may refund the outstanding balance
+    pub deadline: Option<i64>,
+}
+
+/// Release schedule requested at initialization
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum EscrowTerms {
+    /// Whole amount released in one go
+    Standard,
+    /// Ordered partial releases; milestone amounts must sum to the escrow amount
+    Milestones { milestones: Vec<Milestone> },
+    /// Linear unlock of the escrow amount between two unix timestamps
+    Vesting { start: i64, end: i64 },
+    /// Hash time-locked release for cross-chain atomic swaps
+    Hashlock { algorithm: HashAlgorithm, hash: [u8; 32] },
+}
+
+/// Off-chain trade details kept on-chain for compliance and dispute review
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct TradeMetadata {
+    /// ISO 4217 fiat currency code, e.g. `*b"VES"`
+    pub fiat_currency: [u8; 3],
+    /// Fiat amount owed for the trade, in fiat minor units (e.g. cents)
+    pub fiat_amount: u64,
+    /// Agreed fiat price per whole token, in fiat minor units
+    pub price: u64,
+    /// Rail the fiat leg is paid over
+    pub payment_method: PaymentMethod,
+    /// Hash of the off-chain trade terms both parties agreed to
+    pub terms_hash: [u8; 32],
+}
+
+/// Beneficiary's claim about the fiat payment, so the depositor knows which transfer to
+/// look for and an arbiter can compare claims
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct PaymentReference {
+    /// Hash of the bank reference number or Pago Móvil confirmation
+    pub reference_hash: [u8; 32],
+    /// Fiat amount the beneficiary claims to have paid, in fiat minor units
+    pub fiat_amount: u64,
+}
+
+/// Fiat payment rails, in the same order as the backend's `PaymentMethod` enum
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PaymentMethod {
+    Zelle,
+    Venmo,
+    Paypal,
+    BankTransfer,
+    PagoMovil,
+    BinancePay,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
 pub enum EscrowStatus {
-    /// Funds locked in vault, awaiting buyer's off-chain payment
+    /// Funds locked in vault, awaiting the beneficiary's off-chain leg
     Locked,
-    /// Buyer has marked off-chain payment as sent
+    /// Beneficiary has marked the off-chain leg (payment or delivery) as done
     PaymentSent,
-    /// Seller confirmed receipt — funds released to buyer
+    /// Depositor confirmed — funds released to beneficiary
     Released,
-    /// Escrow refunded back to seller
+    /// Escrow refunded back to depositor
     Refunded,
     /// Under dispute — awaiting admin resolution
     Disputed,
 }
 
+/// Two-sided on-chain swap: maker locks `amount_a` of `mint_a`, taker pays `amount_b` of `mint_b`
+#[account]
+#[derive(InitSpace)]
+pub struct SwapState {
+    /// Swap UUID (128-bit, matches DB offer ID)
+    pub swap_id: [u8; 16],
+    /// Maker who deposited token A
+    pub maker: Pubkey,
+    /// Optional counterparty — `None` lets anyone take the swap
+    pub taker: Option<Pubkey>,
+    /// Token the maker deposited
+    pub mint_a: Pubkey,
+    /// Token the maker wants in return
+    pub mint_b: Pubkey,
+    /// Amount of token A locked in the vault (raw, in smallest units)
+    pub amount_a: u64,
+    /// Amount of token B the maker wants (raw, in smallest units)
+    pub amount_b: u64,
+    /// Unix timestamp when the swap was created
+    pub created_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// P2P sell offer (ad): a pool of liquidity that buyers fill partially into child escrows
+#[account]
+#[derive(InitSpace)]
+pub struct Offer {
+    /// Offer UUID (128-bit, matches DB offer ID)
+    pub offer_id: [u8; 16],
+    /// Seller who funds the offer and is the depositor of every child escrow
+    pub seller: Pubkey,
+    /// Token mint (USDC or MVGA)
+    pub mint: Pubkey,
+    /// Unreserved liquidity left in the offer vault (raw, in smallest units)
+    pub available: u64,
+    /// Smallest fill a buyer can take
+    pub min_fill: u64,
+    /// Largest fill a buyer can take
+    pub max_fill: u64,
+    /// Bitmask of accepted payment methods (bit N = payment method id N)
+    pub payment_methods: u32,
+    /// Fiat price per whole token, in fiat minor units (e.g. cents)
+    pub price: u64,
+    /// ISO 4217 fiat currency code, e.g. `*b"VES"`
+    pub fiat_currency: [u8; 3],
+    /// Timeout applied to every child escrow
+    pub timeout_seconds: u64,
+    /// Admin authority copied into every child escrow
+    pub admin: Pubkey,
+    /// Number of fills taken so far
+    pub fill_count: u64,
+    /// Unix timestamp when the offer was created
+    pub created_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Seller-signed P2P order, settled on-chain by the buyer in `fill_signed_order`.
+/// The seller signs `message()` off-chain with their wallet key; nothing is stored
+/// until a buyer fills it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SignedOrder {
+    /// Seller who signed the order and becomes the escrow depositor
+    pub seller: Pubkey,
+    /// Token mint (USDC or MVGA)
+    pub mint: Pubkey,
+    /// Smallest amount a buyer can fill
+    pub min_amount: u64,
+    /// Largest amount a buyer can fill
+    pub max_amount: u64,
+    /// Fiat price per whole token, in fiat minor units (e.g. cents)
+    pub price: u64,
+    /// ISO 4217 fiat currency code, e.g. `*b"VES"`
+    pub fiat_currency: [u8; 3],
+    /// Timeout applied to the resulting escrow
+    pub timeout_seconds: u64,
+    /// Unix timestamp after which the order can no longer be filled
+    pub expires_at: i64,
+    /// Seller-chosen nonce — each (seller, nonce) pair can be filled or cancelled once
+    pub nonce: u64,
+}
+
+impl SignedOrder {
+    /// Domain prefix so an order signature can never be replayed as another message
+    pub const DOMAIN: &'static [u8] = b"mvga-escrow:order:v1";
+
+    /// Bytes the seller signs: domain prefix, program id, then the borsh-encoded order
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32 + 128);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(crate::ID.as_ref());
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+/// Marks a signed-order nonce as used — created when the order is filled or cancelled,
+/// so the same signature can never settle twice
+#[account]
+#[derive(InitSpace)]
+pub struct OrderNonce {
+    /// Seller the nonce belongs to
+    pub seller: Pubkey,
+    /// Nonce from the signed order
+    pub nonce: u64,
+    /// True if the seller revoked the order instead of a buyer filling it
+    pub cancelled: bool,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Depositor-signed authorization to release an escrow, submitted by anyone through
+/// `release_with_voucher` so the depositor doesn't need SOL for fees
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ReleaseVoucher {
+    /// Trade the voucher releases
+    pub trade_id: [u8; 16],
+    /// Escrow state PDA the voucher releases
+    pub escrow: Pubkey,
+    /// `created_at` of the escrow — a recreated escrow at the same PDA gets a new one
+    pub created_at: i64,
+    /// Outstanding escrow balance the depositor signed off on — it shrinks with every
+    /// milestone, so a voucher can never be replayed against a later release step
+    pub amount: u64,
+    /// Unix timestamp after which the voucher can no longer be redeemed
+    pub expires_at: i64,
+}
+
+impl ReleaseVoucher {
+    /// Domain prefix so a voucher signature can never be replayed as another message
+    pub const DOMAIN: &'static [u8] = b"mvga-escrow:release:v2";
+
+    /// Bytes the depositor signs: domain prefix, program id, then the borsh-encoded voucher
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32 + 64);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(crate::ID.as_ref());
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+/// Payment attestor registered by the admin — a backend key that confirms fiat receipt
+/// through bank / Pago Móvil integrations
+#[account]
+#[derive(InitSpace)]
+pub struct Attestor {
+    /// Key that signs payment attestations
+    pub authority: Pubkey,
+    /// Unix timestamp when the attestor was registered
+    pub registered_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Attestor-signed confirmation that the fiat leg of a trade was received,
+/// submitted by anyone through `release_with_attestation`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct PaymentAttestation {
+    /// Trade the payment belongs to
+    pub trade_id: [u8; 16],
+    /// Escrow state PDA being released
+    pub escrow: Pubkey,
+    /// `created_at` of the escrow — a recreated escrow at the same PDA gets a new one
+    pub created_at: i64,
+    /// Release step being confirmed — `next_milestone` at signing time, 0 for standard escrows
+    pub milestone: u8,
+    /// Token amount the confirmed payment covers
+    pub amount: u64,
+}
+
+impl PaymentAttestation {
+    /// Domain prefix so an attestation signature can never be replayed as another message
+    pub const DOMAIN: &'static [u8] = b"mvga-escrow:attestation:v2";
+
+    /// Bytes the attestor signs: domain prefix, program id, then the borsh-encoded attestation
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32 + 64);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(crate::ID.as_ref());
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+/// Scoped delegate letting an ephemeral hot key act for a trader without a wallet prompt
+#[account]
+#[derive(InitSpace)]
+pub struct SessionKey {
+    /// Wallet the session acts for
+    pub owner: Pubkey,
+    /// Ephemeral key allowed to sign in the owner's place
+    pub delegate: Pubkey,
+    /// Bitmask of `SESSION_*` permissions
+    pub permissions: u8,
+    /// Unix timestamp after which the session stops working
+    pub expires_at: i64,
+    /// Most the delegate may release across the whole session (raw, in smallest units)
+    pub spend_cap: u64,
+    /// Released so far through this session
+    pub spent: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SessionKey {
+    /// Checks that `authority` may act for `owner`: either it is the owner itself, or
+    /// `session` is a live session of `owner` delegating `permission` to `authority`.
+    /// Fails with `unauthorized` when a non-owner signs without a session key.
+    pub fn authorize(
+        session: Option<&SessionKey>,
+        authority: &Pubkey,
+        owner: &Pubkey,
+        permission: u8,
+        unauthorized: EscrowError,
+    ) -> Result<()> {
+        if authority == owner {
+            return Ok(());
+        }
+        let session = session.ok_or(unauthorized)?;
+        require!(
+            session.owner == *owner
+                && session.delegate == *authority
+                && session.permissions & permission != 0,
+            EscrowError::InvalidSessionKey
+        );
+        require!(
+            Clock::get()?.unix_timestamp < session.expires_at,
+            EscrowError::SessionExpired
+        );
+        Ok(())
+    }
+}
+
+/// Program-owned pool that fronts escrow rent for users holding only tokens. Sponsored
+/// escrows return their rent to the pool on close; the treasury tops it up from platform fees.
+#[account]
+#[derive(InitSpace)]
+pub struct RentSponsor {
+    /// Relayer that pays rent up front and is reimbursed by the pool in the same instruction
+    pub operator: Pubkey,
+    /// Lamports deposited through `fund_rent_sponsor`
+    pub total_funded: u64,
+    /// Lamports reimbursed to the operator for sponsored escrows
+    pub total_fronted: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Program-wide configuration: who holds each privileged role and whether the program is
+/// paused. Split out of the single `AUTHORIZED_ADMIN` so a hot key can pause the program
+/// without holding the multisig that controls treasury settings.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    /// Grants and revokes every role, including handing itself over
+    pub config_admin: Pubkey,
+    /// Fast hot key that can pause the program
+    pub pauser: Option<Pubkey>,
+    /// Resolves disputes
+    pub arbiter: Option<Pubkey>,
+    /// Controls fees and the rent sponsor
+    pub fee_manager: Option<Pubkey>,
+    /// Controls which mints may be escrowed
+    pub mint_manager: Option<Pubkey>,
+    /// When set, no new escrows can be opened
+    pub paused: bool,
+    /// Unix timestamp of the last pause (0 when never paused)
+    pub paused_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+    /// Seconds a proposed config change must wait before it can be executed
+    pub config_delay: i64,
+    /// Id handed to the next `PendingConfigChange`
+    pub next_change_id: u64,
+    /// Seconds the program must stay paused before anyone can call `emergency_withdraw`
+    pub emergency_grace_period: i64,
+    /// Platform fee taken on release, in basis points of the released amount
+    pub fee_bps: u16,
+    /// Share of the platform fee paid to each party's referrer, in basis points of the fee
+    pub referral_share_bps: u16,
+    /// Wallet whose token accounts collect the platform fee
+    pub fee_recipient: Pubkey,
+    /// Zeroed space for future settings
+    pub reserved: [u8; CONFIG_RESERVED_SPACE],
+}
+
+impl Config {
+    /// Current holder of `role`, if any
+    pub fn holder(&self, role: Role) -> Option<Pubkey> {
+        match role {
+            Role::ConfigAdmin => Some(self.config_admin),
+            Role::Pauser => self.pauser,
+            Role::Arbiter => self.arbiter,
+            Role::FeeManager => self.fee_manager,
+            Role::MintManager => self.mint_manager,
+        }
+    }
+
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        self.holder(role) == Some(*key)
+    }
+
+    /// Assigns `role` to `holder`, returning the previous holder
+    pub fn set_holder(&mut self, role: Role, holder: Option<Pubkey>) -> Option<Pubkey> {
+        let previous = self.holder(role);
+        match role {
+            Role::ConfigAdmin => {
+                if let Some(holder) = holder {
+                    self.config_admin = holder;
+                }
+            }
+            Role::Pauser => self.pauser = holder,
+            Role::Arbiter => self.arbiter = holder,
+            Role::FeeManager => self.fee_manager = holder,
+            Role::MintManager => self.mint_manager = holder,
+        }
+        previous
+    }
+
+    /// Whether changing `role` has to go through the config change queue. The pauser can
+    /// always be rotated directly so a leaked hot key is never stuck behind the delay.
+    pub fn requires_timelock(&self, role: Role) -> bool {
+        self.config_delay > 0 && role != Role::Pauser
+    }
+
+    /// Whether the program has been paused long enough for users to pull their funds out
+    pub fn emergency_exit_open(&self, now: i64) -> bool {
+        self.paused && now >= self.paused_at.saturating_add(self.emergency_grace_period)
+    }
+
+    /// Platform fee on releasing `amount`, and the part of it owed to each referrer
+    pub fn fee_split(&self, amount: u64) -> (u64, u64) {
+        let fee = (amount as u128 * self.fee_bps as u128 / 10_000) as u64;
+        let referral_share = (fee as u128 * self.referral_share_bps as u128 / 10_000) as u64;
+        (fee, referral_share)
+    }
+
+    /// Applies an executed config change
+    pub fn apply(&mut self, change: &ConfigChange) -> Result<()> {
+        change.validate()?;
+        match *change {
+            ConfigChange::GrantRole { role, holder } => {
+                self.set_holder(role, Some(holder));
+            }
+            ConfigChange::RevokeRole { role } => {
+                self.set_holder(role, None).ok_or(EscrowError::RoleNotAssigned)?;
+            }
+            ConfigChange::SetConfigDelay { seconds } => self.config_delay = seconds,
+            ConfigChange::SetEmergencyGracePeriod { seconds } => {
+                self.emergency_grace_period = seconds;
+            }
+            ConfigChange::SetFees {
+                fee_bps,
+                referral_share_bps,
+                fee_recipient,
+            } => {
+                self.fee_bps = fee_bps;
+                self.referral_share_bps = referral_share_bps;
+                self.fee_recipient = fee_recipient;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Privileged roles stored in `Config`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Role {
+    ConfigAdmin,
+    Pauser,
+    Arbiter,
+    FeeManager,
+    MintManager,
+}
+
+/// A change to `Config` that only takes effect once its delay has passed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ConfigChange {
+    GrantRole { role: Role, holder: Pubkey },
+    RevokeRole { role: Role },
+    SetConfigDelay { seconds: i64 },
+    SetEmergencyGracePeriod { seconds: i64 },
+    SetFees {
+        fee_bps: u16,
+        referral_share_bps: u16,
+        fee_recipient: Pubkey,
+    },
+}
+
+impl ConfigChange {
+    /// Rejects changes that could never be applied
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            ConfigChange::RevokeRole { role } => {
+                require!(role != Role::ConfigAdmin, EscrowError::ConfigAdminNotRevocable);
+            }
+            ConfigChange::SetConfigDelay { seconds } => {
+                require!(
+                    (0..=MAX_CONFIG_DELAY).contains(&seconds),
+                    EscrowError::ConfigDelayTooLong
+                );
+            }
+            ConfigChange::SetEmergencyGracePeriod { seconds } => {
+                require!(
+                    seconds > 0 && seconds <= MAX_EMERGENCY_GRACE_PERIOD,
+                    EscrowError::InvalidGracePeriod
+                );
+            }
+            ConfigChange::SetFees {
+                fee_bps,
+                referral_share_bps,
+                ..
+            } => {
+                require!(
+                    fee_bps <= MAX_FEE_BPS && referral_share_bps <= MAX_REFERRAL_SHARE_BPS,
+                    EscrowError::InvalidFeeConfig
+                );
+            }
+            ConfigChange::GrantRole { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+/// Queued config change, public so traders with open escrows can see it coming
+#[account]
+#[derive(InitSpace)]
+pub struct PendingConfigChange {
+    /// Sequential id, also part of the PDA seeds
+    pub id: u64,
+    pub change: ConfigChange,
+    /// Config admin that proposed the change
+    pub proposer: Pubkey,
+    pub proposed_at: i64,
+    /// Earliest unix timestamp the change can be executed
+    pub executable_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Marks a wallet the platform may not trade with. Existence of the PDA is the flag;
+/// checks derive it from the wallet and require it to be empty.
+#[account]
+#[derive(InitSpace)]
+pub struct Denylisted {
+    pub wallet: Pubkey,
+    /// Config admin that added the wallet
+    pub added_by: Pubkey,
+    pub added_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Backend key allowed to issue KYC attestations
+#[account]
+#[derive(InitSpace)]
+pub struct KycAttestor {
+    /// Key that signs `issue_kyc_attestation`
+    pub authority: Pubkey,
+    /// Unix timestamp when the attestor was registered
+    pub registered_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Identity verification of a wallet, issued by a registered KYC attestor
+#[account]
+#[derive(InitSpace)]
+pub struct KycAttestation {
+    pub wallet: Pubkey,
+    /// KYC attestor authority that issued (or last re-issued) the attestation
+    pub attestor: Pubkey,
+    /// Verification tier — higher means more thorough checks
+    pub level: u8,
+    pub issued_at: i64,
+    /// Unix timestamp after which the attestation no longer counts
+    pub expires_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl KycAttestation {
+    /// Whether the attestation still counts: unexpired, and issued by `attestor` while it
+    /// was registered. Removing an attestor voids everything it issued, and re-registering
+    /// it does not bring those attestations back.
+    pub fn is_current(&self, attestor: Option<&KycAttestor>, now: i64) -> bool {
+        now < self.expires_at
+            && attestor.is_some_and(|attestor| {
+                attestor.authority == self.attestor && attestor.registered_at <= self.issued_at
+            })
+    }
+
+    pub fn is_valid(&self, min_level: u8, attestor: Option<&KycAttestor>, now: i64) -> bool {
+        self.level >= min_level && self.is_current(attestor, now)
+    }
+}
+
+/// Per-mint trading policy, managed by the mint manager. Mints without one are ungated.
+#[account]
+#[derive(InitSpace)]
+pub struct MintPolicy {
+    pub mint: Pubkey,
+    /// Escrows above this amount need a KYC attestation for both parties (`None` = never)
+    pub kyc_threshold: Option<u64>,
+    /// Minimum KYC level those attestations must carry
+    pub kyc_level: u8,
+    /// PDA bump seed
+    pub bump: u8,
+    /// Rolling volume caps per wallet, one tier per KYC level
+    pub volume_limits: [VolumeLimit; VOLUME_LIMIT_TIERS],
+    /// Most that may sit in this mint's escrow, offer and swap vaults at once (`None` = uncapped)
+    pub tvl_cap: Option<u64>,
+    /// Zeroed space for future per-mint settings
+    pub reserved: [u8; MINT_POLICY_RESERVED_SPACE],
+}
+
+impl MintPolicy {
+    /// Reads the policy at `info`, which callers derive from the mint's seeds. An empty
+    /// account means the mint has no policy.
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        let data = info.try_borrow_data()?;
+        Ok(Some(MintPolicy::try_deserialize(&mut &data[..])?))
+    }
+
+    /// Whether an escrow of `amount` needs KYC attestations
+    pub fn requires_kyc(&self, amount: u64) -> bool {
+        self.kyc_threshold.is_some_and(|threshold| amount > threshold)
+    }
+
+    /// Checks both parties of an escrow of `amount`, each given as its attestation and the
+    /// registry entry of that attestation's attestor
+    pub fn check_kyc(
+        &self,
+        amount: u64,
+        parties: [(Option<&KycAttestation>, Option<&KycAttestor>); 2],
+        now: i64,
+    ) -> Result<()> {
+        if !self.requires_kyc(amount) {
+            return Ok(());
+        }
+        for (kyc, attestor) in parties {
+            require!(
+                kyc.is_some_and(|kyc| kyc.is_valid(self.kyc_level, attestor, now)),
+                EscrowError::KycRequired
+            );
+        }
+        Ok(())
+    }
+
+    /// Volume caps for a wallet verified at `kyc_level`
+    pub fn volume_limit(&self, kyc_level: u8) -> VolumeLimit {
+        self.volume_limits[(kyc_level as usize).min(VOLUME_LIMIT_TIERS - 1)]
+    }
+}
+
+/// Rolling volume caps for one KYC tier (`None` = uncapped)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct VolumeLimit {
+    pub daily: Option<u64>,
+    pub monthly: Option<u64>,
+}
+
+impl VolumeLimit {
+    /// Whether either window has a cap, so the wallet's volume has to be tracked
+    pub fn is_capped(&self) -> bool {
+        self.daily.is_some() || self.monthly.is_some()
+    }
+}
+
+/// A wallet's escrowed volume in one mint, in time buckets so the 24h and 30-day windows
+/// roll forward without rewriting history
+#[account]
+#[derive(InitSpace)]
+pub struct VolumeTracker {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    /// Volume per hour, indexed by hour number modulo 24
+    pub hourly: [u64; DAILY_VOLUME_BUCKETS],
+    /// Volume per day, indexed by day number modulo 30
+    pub daily: [u64; MONTHLY_VOLUME_BUCKETS],
+    /// Hour number (unix time / 3600) of the last recorded trade
+    pub last_hour: i64,
+    /// Day number (unix time / 86400) of the last recorded trade
+    pub last_day: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl VolumeTracker {
+    /// Clears the buckets that fell out of their window since the last trade
+    pub fn roll(&mut self, now: i64) {
+        let hour = now.div_euclid(3600);
+        let day = now.div_euclid(86_400);
+        Self::expire(&mut self.hourly, self.last_hour, hour);
+        Self::expire(&mut self.daily, self.last_day, day);
+        self.last_hour = hour;
+        self.last_day = day;
+    }
+
+    fn expire(buckets: &mut [u64], last: i64, current: i64) {
+        let len = buckets.len() as i64;
+        if current - last >= len {
+            buckets.fill(0);
+            return;
+        }
+        for period in (last + 1)..=current {
+            buckets[period.rem_euclid(len) as usize] = 0;
+        }
+    }
+
+    /// Volume over the last 24 hours. Call `roll` first.
+    pub fn rolling_daily(&self) -> u64 {
+        self.hourly.iter().sum()
+    }
+
+    /// Volume over the last 30 days. Call `roll` first.
+    pub fn rolling_monthly(&self) -> u64 {
+        self.daily.iter().sum()
+    }
+
+    /// Rolls the windows forward, checks `amount` against `limit` and records it
+    pub fn record(&mut self, amount: u64, limit: VolumeLimit, now: i64) -> Result<()> {
+        self.roll(now);
+        self.rolling_daily()
+            .checked_add(amount)
+            .filter(|daily| limit.daily.is_none_or(|cap| *daily <= cap))
+            .ok_or(EscrowError::DailyVolumeExceeded)?;
+        self.rolling_monthly()
+            .checked_add(amount)
+            .filter(|monthly| limit.monthly.is_none_or(|cap| *monthly <= cap))
+            .ok_or(EscrowError::MonthlyVolumeExceeded)?;
+
+        self.hourly[self.last_hour.rem_euclid(DAILY_VOLUME_BUCKETS as i64) as usize] += amount;
+        self.daily[self.last_day.rem_euclid(MONTHLY_VOLUME_BUCKETS as i64) as usize] += amount;
+        Ok(())
+    }
+}
+
+/// Platform statistics for one mint, kept current by every instruction that moves escrowed
+/// funds or changes an escrow's status. Updates saturate instead of failing so escrows
+/// opened before the account existed can still settle; only deposits fail, on the TVL cap.
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalStats {
+    pub mint: Pubkey,
+    /// Tokens currently held in escrow, offer and swap vaults
+    pub total_locked: u64,
+    /// Open escrows in `Locked`
+    pub locked: u64,
+    /// Open escrows in `PaymentSent`
+    pub payment_sent: u64,
+    /// Open escrows in `Disputed`
+    pub disputed: u64,
+    /// Escrows ever opened
+    pub total_escrows: u64,
+    /// Tokens ever paid out to beneficiaries
+    pub cumulative_volume: u64,
+    /// Platform fees ever collected
+    pub cumulative_fees: u64,
+    /// PDA bump seed
+    pub bump: u8,
+    /// Zeroed space for future counters
+    pub reserved: [u8; GLOBAL_STATS_RESERVED_SPACE],
+}
+
+impl GlobalStats {
+    fn open_count(&mut self, status: EscrowStatus) -> Option<&mut u64> {
+        match status {
+            EscrowStatus::Locked => Some(&mut self.locked),
+            EscrowStatus::PaymentSent => Some(&mut self.payment_sent),
+            EscrowStatus::Disputed => Some(&mut self.disputed),
+            EscrowStatus::Released | EscrowStatus::Refunded => None,
+        }
+    }
+
+    /// A new escrow locked `amount`, within the mint's `tvl_cap`
+    pub fn opened(&mut self, amount: u64, tvl_cap: Option<u64>) -> Result<()> {
+        self.deposited(amount, tvl_cap)?;
+        self.total_escrows = self.total_escrows.saturating_add(1);
+        self.locked = self.locked.saturating_add(1);
+        Ok(())
+    }
+
+    /// A new escrow was filled from an offer. Its tokens were counted, and held to the
+    /// `tvl_cap`, when the seller funded the offer, so they aren't counted again.
+    pub fn opened_from_offer(&mut self) {
+        self.total_escrows = self.total_escrows.saturating_add(1);
+        self.locked = self.locked.saturating_add(1);
+    }
+
+    /// An escrow moved between statuses; `Released` and `Refunded` close it
+    pub fn moved(&mut self, from: EscrowStatus, to: EscrowStatus) {
+        if let Some(count) = self.open_count(from) {
+            *count = count.saturating_sub(1);
+        }
+        if let Some(count) = self.open_count(to) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Tokens added to an escrow, offer or swap vault. Every deposit path goes through
+    /// here, so this is where the mint's `tvl_cap` is enforced.
+    pub fn deposited(&mut self, amount: u64, tvl_cap: Option<u64>) -> Result<()> {
+        self.total_locked = self
+            .total_locked
+            .checked_add(amount)
+            .filter(|total| tvl_cap.is_none_or(|cap| *total <= cap))
+            .ok_or(EscrowError::TvlCapExceeded)?;
+        Ok(())
+    }
+
+    /// Tokens paid out of a vault to the beneficiary, or to the taker of a swap
+    pub fn paid_out(&mut self, amount: u64) {
+        self.total_locked = self.total_locked.saturating_sub(amount);
+        self.cumulative_volume = self.cumulative_volume.saturating_add(amount);
+    }
+
+    /// Tokens returned from a vault to whoever funded it
+    pub fn returned(&mut self, amount: u64) {
+        self.total_locked = self.total_locked.saturating_sub(amount);
+    }
+
+    /// Platform fee taken out of a release, referral shares included
+    pub fn collected_fee(&mut self, fee: u64) {
+        self.cumulative_fees = self.cumulative_fees.saturating_add(fee);
+    }
+}
+
+/// A wallet's referral record for one mint, created when it registers as a referrer.
+/// Escrows name the referrer's wallet; releases pay its fee share into its token account.
+#[account]
+#[derive(InitSpace)]
+pub struct ReferrerStats {
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+    /// Escrows opened with this wallet as a party's referrer
+    pub referrals: u64,
+    /// Tokens released on escrows it referred
+    pub referred_volume: u64,
+    /// Platform fee shares paid to it
+    pub earnings: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ReferrerStats {
+    /// Credits this referrer with a new escrow between `parties`, returning the wallet to
+    /// store on the escrow. Parties cannot refer themselves.
+    pub fn refer(&mut self, parties: [Pubkey; 2]) -> Result<Pubkey> {
+        require!(!parties.contains(&self.referrer), EscrowError::InvalidReferrer);
+        self.referrals = self.referrals.saturating_add(1);
+        Ok(self.referrer)
+    }
+}
+
 // ─── Events ────────────────────────────────────────────────────────────
 
 #[event]
 pub struct EscrowInitialized {
     pub trade_id: [u8; 16],
-    pub seller: Pubkey,
-    pub buyer: Pubkey,
+    pub depositor: Pubkey,
+    pub beneficiary: Pubkey,
     pub mint: Pubkey,
     pub amount: u64,
     pub timeout_seconds: u64,
+    pub direction: EscrowDirection,
+    pub milestone_count: u8,
+    pub rent_payer: Pubkey,
+    pub metadata: Option<TradeMetadata>,
 }
 
 #[event]
 pub struct PaymentMarked {
     pub trade_id: [u8; 16],
-    pub buyer: Pubkey,
+    pub beneficiary: Pubkey,
+    pub payout_account: Option<Pubkey>,
+    pub payment_reference: Option<PaymentReference>,
 }
 
 #[event]
 pub struct EscrowReleased {
     pub trade_id: [u8; 16],
-    pub seller: Pubkey,
-    pub buyer: Pubkey,
+    pub depositor: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    /// Attestor whose payment confirmation triggered the release, if any
+    pub attestor: Option<Pubkey>,
+}
+
+#[event]
+pub struct MilestoneReleased {
+    pub trade_id: [u8; 16],
+    pub index: u8,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct VestedClaimed {
+    pub trade_id: [u8; 16],
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct VestingCancelled {
+    pub trade_id: [u8; 16],
+    pub depositor: Pubkey,
+    pub vested_paid: u64,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct HashlockClaimed {
+    pub trade_id: [u8; 16],
+    pub beneficiary: Pubkey,
     pub amount: u64,
+    /// Revealed secret — lets the counterparty chain complete its side of the swap
+    pub preimage: Vec<u8>,
 }
 
 #[event]
 pub struct EscrowRefunded {
     pub trade_id: [u8; 16],
-    pub seller: Pubkey,
+    pub depositor: Pubkey,
     pub amount: u64,
 }
 
@@ -82,5 +1248,661 @@ pub struct DisputeFiled {
 pub struct DisputeResolved {
     pub trade_id: [u8; 16],
     pub admin: Pubkey,
-    pub resolution: u8, // 0 = ReleaseToBuyer, 1 = RefundToSeller
+    pub resolution: u8, // 0 = ReleaseToBeneficiary, 1 = RefundToDepositor
+}
+
+#[event]
+pub struct SwapCreated {
+    pub swap_id: [u8; 16],
+    pub maker: Pubkey,
+    pub taker: Option<Pubkey>,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct SwapTaken {
+    pub swap_id: [u8; 16],
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct SwapCancelled {
+    pub swap_id: [u8; 16],
+    pub maker: Pubkey,
+    pub amount_a: u64,
+}
+
+#[event]
+pub struct OfferCreated {
+    pub offer_id: [u8; 16],
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub min_fill: u64,
+    pub max_fill: u64,
+    pub payment_methods: u32,
+    pub price: u64,
+    pub fiat_currency: [u8; 3],
+}
+
+#[event]
+pub struct OfferFilled {
+    pub offer_id: [u8; 16],
+    pub trade_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub payment_method: u8,
+    pub available: u64,
+}
+
+#[event]
+pub struct OfferLiquidityChanged {
+    pub offer_id: [u8; 16],
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub available: u64,
+}
+
+#[event]
+pub struct OfferClosed {
+    pub offer_id: [u8; 16],
+    pub seller: Pubkey,
+    pub withdrawn: u64,
+}
+
+#[event]
+pub struct SignedOrderFilled {
+    pub trade_id: [u8; 16],
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SignedOrderCancelled {
+    pub seller: Pubkey,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct AttestorRegistered {
+    pub attestor: Pubkey,
+}
+
+#[event]
+pub struct AttestorRemoved {
+    pub attestor: Pubkey,
+}
+
+#[event]
+pub struct SessionKeyCreated {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub permissions: u8,
+    pub expires_at: i64,
+    pub spend_cap: u64,
+}
+
+#[event]
+pub struct SessionKeyRevoked {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub spent: u64,
+}
+
+#[event]
+pub struct RentSponsorConfigured {
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct RentSponsorFunded {
+    pub funder: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct RentSponsored {
+    pub trade_id: [u8; 16],
+    pub sponsor: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct PayoutAccountSet {
+    pub trade_id: [u8; 16],
+    pub beneficiary: Pubkey,
+    pub account: Pubkey,
+}
+
+#[event]
+pub struct RefundAccountSet {
+    pub trade_id: [u8; 16],
+    pub depositor: Pubkey,
+    pub account: Pubkey,
+}
+
+#[event]
+pub struct EscrowAmended {
+    pub trade_id: [u8; 16],
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub old_timeout_seconds: u64,
+    pub new_timeout_seconds: u64,
+}
+
+#[event]
+pub struct TimeoutExtended {
+    pub trade_id: [u8; 16],
+    pub old_timeout_seconds: u64,
+    pub new_timeout_seconds: u64,
+    pub refund_deadline: i64,
+}
+
+#[event]
+pub struct PaymentReferenceUpdated {
+    pub trade_id: [u8; 16],
+    pub beneficiary: Pubkey,
+    pub previous: Option<PaymentReference>,
+    pub payment_reference: PaymentReference,
+}
+
+#[event]
+pub struct EscrowMigrated {
+    pub trade_id: [u8; 16],
+    pub escrow: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct ConfigInitialized {
+    pub config_admin: Pubkey,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub role: Role,
+    pub holder: Pubkey,
+    pub previous: Option<Pubkey>,
+    pub granted_by: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub role: Role,
+    pub holder: Pubkey,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct ProgramPaused {
+    pub pauser: Pubkey,
+    pub paused_at: i64,
+}
+
+#[event]
+pub struct ProgramUnpaused {
+    pub config_admin: Pubkey,
+}
+
+#[event]
+pub struct ConfigChangeProposed {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub proposer: Pubkey,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct EmergencyWithdrawn {
+    pub trade_id: [u8; 16],
+    pub depositor: Pubkey,
+    pub beneficiary: Pubkey,
+    /// Status the escrow was in when the program was paused
+    pub status: EscrowStatus,
+    pub to_depositor: u64,
+    pub to_beneficiary: u64,
+}
+
+#[event]
+pub struct WalletDenylisted {
+    pub wallet: Pubkey,
+    pub added_by: Pubkey,
+}
+
+#[event]
+pub struct WalletUndenylisted {
+    pub wallet: Pubkey,
+    pub removed_by: Pubkey,
+}
+
+#[event]
+pub struct DenylistedEscrowDisputed {
+    pub trade_id: [u8; 16],
+    pub wallet: Pubkey,
+    pub config_admin: Pubkey,
+}
+
+#[event]
+pub struct KycAttestorRegistered {
+    pub attestor: Pubkey,
+}
+
+#[event]
+pub struct KycAttestorRemoved {
+    pub attestor: Pubkey,
+}
+
+#[event]
+pub struct KycAttested {
+    pub wallet: Pubkey,
+    pub attestor: Pubkey,
+    pub level: u8,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct KycAttestationRevoked {
+    pub wallet: Pubkey,
+    pub attestor: Pubkey,
+}
+
+#[event]
+pub struct KycRequirementSet {
+    pub mint: Pubkey,
+    pub kyc_threshold: Option<u64>,
+    pub kyc_level: u8,
+    pub mint_manager: Pubkey,
+}
+
+#[event]
+pub struct VolumeLimitsSet {
+    pub mint: Pubkey,
+    pub volume_limits: [VolumeLimit; VOLUME_LIMIT_TIERS],
+    pub mint_manager: Pubkey,
+}
+
+#[event]
+pub struct TvlCapSet {
+    pub mint: Pubkey,
+    pub tvl_cap: Option<u64>,
+    pub mint_manager: Pubkey,
+}
+
+#[event]
+pub struct FeesSet {
+    pub fee_bps: u16,
+    pub referral_share_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub fee_manager: Pubkey,
+}
+
+#[event]
+pub struct ReferrerRegistered {
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct PlatformFeeCollected {
+    pub trade_id: [u8; 16],
+    pub mint: Pubkey,
+    /// Whole fee, referral shares included
+    pub fee: u64,
+    /// Part of the fee paid to referrers
+    pub referral_paid: u64,
+}
+
+#[event]
+pub struct ReferralPaid {
+    pub trade_id: [u8; 16],
+    pub referrer: Pubkey,
+    pub amount: u64,
+    /// Amount released on the escrow
+    pub volume: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy() -> EscrowStateV0 {
+        EscrowStateV0 {
+            trade_id: [7; 16],
+            seller: Pubkey::new_from_array([1; 32]),
+            buyer: Pubkey::new_from_array([2; 32]),
+            mint: Pubkey::new_from_array([3; 32]),
+            amount: 1_000_000,
+            status: EscrowStatus::PaymentSent,
+            admin: Pubkey::new_from_array([4; 32]),
+            locked_at: 1_700_000_000,
+            timeout_seconds: 7200,
+            bump: 254,
+        }
+    }
+
+    /// Largest possible value of every variable-size field
+    fn fully_populated() -> EscrowState {
+        let mut escrow = legacy().upgrade();
+        escrow.mode = EscrowMode::Hashlock {
+            algorithm: HashAlgorithm::Sha256,
+            hash: [5; 32],
+        };
+        escrow.milestones = vec![
+            Milestone {
+                amount: 1,
+                deadline: Some(1),
+            };
+            MAX_MILESTONES
+        ];
+        escrow.payout_account = Some(Pubkey::new_from_array([6; 32]));
+        escrow.refund_account = Some(Pubkey::new_from_array([8; 32]));
+        escrow.metadata = Some(TradeMetadata {
+            fiat_currency: *b"VES",
+            fiat_amount: 36_500,
+            price: 36_500,
+            payment_method: PaymentMethod::PagoMovil,
+            terms_hash: [9; 32],
+        });
+        escrow.payment_reference = Some(PaymentReference {
+            reference_hash: [10; 32],
+            fiat_amount: 36_500,
+        });
+        escrow.depositor_referrer = Some(Pubkey::new_from_array([11; 32]));
+        escrow.beneficiary_referrer = Some(Pubkey::new_from_array([12; 32]));
+        escrow.created_at = 1_699_990_000;
+        escrow
+    }
+
+    fn serialize(escrow: &EscrowState) -> Vec<u8> {
+        let mut data = Vec::new();
+        escrow.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn escrow_state_space_is_locked() {
+        // Changing either number breaks every escrow already on chain at that layout
+        assert_eq!(EscrowStateV0::INIT_SPACE, 170);
+        assert_eq!(EscrowState::INIT_SPACE, 626);
+    }
+
+    #[test]
+    fn fully_populated_escrow_fills_init_space() {
+        let data = serialize(&fully_populated());
+        assert_eq!(data.len(), 8 + EscrowState::INIT_SPACE);
+        assert_eq!(&data[..8], EscrowState::DISCRIMINATOR);
+    }
+
+    #[test]
+    fn fixed_prefix_offsets_are_locked() {
+        let data = serialize(&fully_populated());
+        let body = &data[8..];
+        assert_eq!(&body[0..16], &[7; 16]); // trade_id
+        assert_eq!(&body[16..48], &[1; 32]); // depositor
+        assert_eq!(&body[48..80], &[2; 32]); // beneficiary
+        assert_eq!(&body[80..112], &[3; 32]); // mint
+        assert_eq!(&body[112..120], &1_000_000u64.to_le_bytes()); // amount
+        assert_eq!(body[120], 1); // status = PaymentSent
+        assert_eq!(&body[121..153], &[4; 32]); // admin
+        assert_eq!(&body[153..161], &1_700_000_000i64.to_le_bytes()); // locked_at
+        assert_eq!(&body[161..169], &7200u64.to_le_bytes()); // timeout_seconds
+        assert_eq!(body[169], 254); // bump
+        assert_eq!(body[170], 0); // direction = SellerFunded
+        assert_eq!(body[171], 3); // mode = Hashlock
+    }
+
+    #[test]
+    fn version_and_reserved_close_the_layout() {
+        // Fields carved out of the reserved space sit between the version and the padding
+        let data = serialize(&fully_populated());
+        let tail = &data[data.len() - ESCROW_RESERVED_SPACE - 75..];
+        assert_eq!(tail[0], ESCROW_VERSION);
+        assert_eq!(tail[1], 1); // depositor_referrer = Some
+        assert_eq!(&tail[2..34], &[11; 32]);
+        assert_eq!(tail[34], 1); // beneficiary_referrer = Some
+        assert_eq!(&tail[35..67], &[12; 32]);
+        assert_eq!(&tail[67..75], &1_699_990_000i64.to_le_bytes()); // created_at
+        assert!(tail[75..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn carved_fields_read_as_empty_from_zeroed_reserved_space() {
+        // A v1 escrow written before any field was carved out: the version byte followed
+        // by nothing but zeros up to the end of the account
+        let mut data = serialize(&legacy().upgrade());
+        data.truncate(data.len() - ESCROW_RESERVED_SPACE - 10);
+        assert_eq!(data.last(), Some(&ESCROW_VERSION));
+        data.resize(8 + EscrowState::INIT_SPACE, 0);
+        let escrow = EscrowState::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(escrow.version, ESCROW_VERSION);
+        assert!(escrow.depositor_referrer.is_none() && escrow.beneficiary_referrer.is_none());
+        assert_eq!(escrow.created_at, 0);
+        // Every carved field writes its unset value back as zeros, so a field that can't
+        // round-trip through zeroed space fails here
+        let written = serialize(&escrow);
+        assert_eq!(written[..], data[..written.len()]);
+    }
+
+    #[test]
+    fn reserved_tail_moves_with_the_variable_length_fields() {
+        // The tail follows the milestones and optional fields, so it only lines up between
+        // accounts once each is deserialized as a whole
+        let minimal = serialize(&legacy().upgrade());
+        let full = serialize(&fully_populated());
+        assert!(minimal.len() < full.len());
+        for data in [minimal, full] {
+            let mut account = data.clone();
+            account.resize(8 + EscrowState::INIT_SPACE, 0);
+            let escrow = EscrowState::try_deserialize(&mut &account[..]).unwrap();
+            assert_eq!(serialize(&escrow), data);
+        }
+    }
+
+    #[test]
+    fn launch_layout_is_a_prefix_of_the_upgraded_layout() {
+        let mut legacy_data = EscrowState::DISCRIMINATOR.to_vec();
+        legacy().serialize(&mut legacy_data).unwrap();
+        assert_eq!(legacy_data.len(), 8 + EscrowStateV0::INIT_SPACE);
+
+        let upgraded = serialize(&legacy().upgrade());
+        assert_eq!(&upgraded[..legacy_data.len()], &legacy_data[..]);
+    }
+
+    #[test]
+    fn upgraded_escrow_round_trips() {
+        let data = serialize(&legacy().upgrade());
+        let escrow = EscrowState::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(escrow.depositor, Pubkey::new_from_array([1; 32]));
+        assert_eq!(escrow.beneficiary, Pubkey::new_from_array([2; 32]));
+        assert_eq!(escrow.rent_payer, escrow.depositor);
+        assert!(escrow.status == EscrowStatus::PaymentSent);
+        assert!(escrow.mode == EscrowMode::Standard && escrow.milestones.is_empty());
+        assert_eq!(escrow.version, ESCROW_VERSION);
+        assert_eq!(escrow.created_at, escrow.locked_at);
+    }
+
+    #[test]
+    fn opened_at_falls_back_to_locked_at_before_created_at_was_recorded() {
+        let mut escrow = legacy().upgrade();
+        escrow.locked_at += 3600; // a milestone release restarted the clock
+        assert_eq!(escrow.opened_at(), 1_700_000_000);
+        escrow.created_at = 0;
+        assert_eq!(escrow.opened_at(), escrow.locked_at);
+    }
+
+    fn tracker() -> VolumeTracker {
+        VolumeTracker {
+            wallet: Pubkey::new_from_array([1; 32]),
+            mint: Pubkey::new_from_array([3; 32]),
+            hourly: [0; DAILY_VOLUME_BUCKETS],
+            daily: [0; MONTHLY_VOLUME_BUCKETS],
+            last_hour: 0,
+            last_day: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn volume_windows_roll_forward() {
+        const HOUR: i64 = 3600;
+        const DAY: i64 = 24 * HOUR;
+        let start = 1_700_000_000;
+        let mut volume = tracker();
+        volume.record(100, VolumeLimit::default(), start).unwrap();
+        volume.record(50, VolumeLimit::default(), start + 2 * HOUR).unwrap();
+
+        volume.roll(start + 23 * HOUR);
+        assert_eq!(volume.rolling_daily(), 150);
+
+        volume.roll(start + DAY + HOUR);
+        assert_eq!(volume.rolling_daily(), 50);
+        assert_eq!(volume.rolling_monthly(), 150);
+
+        volume.roll(start + 31 * DAY);
+        assert_eq!(volume.rolling_daily(), 0);
+        assert_eq!(volume.rolling_monthly(), 0);
+    }
+
+    #[test]
+    fn volume_limits_reject_without_recording() {
+        let start = 1_700_000_000;
+        let limit = VolumeLimit {
+            daily: Some(100),
+            monthly: Some(150),
+        };
+        let mut volume = tracker();
+        volume.record(60, limit, start).unwrap();
+        assert!(volume.record(50, limit, start + 60).is_err());
+        assert_eq!(volume.rolling_daily(), 60);
+
+        volume.record(60, limit, start + 25 * 3600).unwrap();
+        assert!(volume.record(40, limit, start + 2 * 24 * 3600).is_err());
+        assert_eq!(volume.rolling_monthly(), 120);
+    }
+
+    #[test]
+    fn global_stats_follow_escrow_lifecycle() {
+        let mut stats = GlobalStats {
+            mint: Pubkey::new_from_array([3; 32]),
+            total_locked: 0,
+            locked: 0,
+            payment_sent: 0,
+            disputed: 0,
+            total_escrows: 0,
+            cumulative_volume: 0,
+            cumulative_fees: 0,
+            bump: 255,
+            reserved: [0; GLOBAL_STATS_RESERVED_SPACE],
+        };
+        stats.opened(100, None).unwrap();
+        stats.opened(40, Some(140)).unwrap();
+        // Nothing is counted when the cap rejects a deposit
+        assert!(stats.opened(1, Some(140)).is_err());
+        assert!(stats.deposited(1, Some(140)).is_err());
+        stats.moved(EscrowStatus::Locked, EscrowStatus::PaymentSent);
+        stats.paid_out(100);
+        stats.moved(EscrowStatus::PaymentSent, EscrowStatus::Released);
+        assert_eq!((stats.locked, stats.payment_sent, stats.total_locked), (1, 0, 40));
+        assert_eq!((stats.total_escrows, stats.cumulative_volume), (2, 100));
+
+        stats.moved(EscrowStatus::Locked, EscrowStatus::Disputed);
+        stats.returned(40);
+        stats.moved(EscrowStatus::Disputed, EscrowStatus::Refunded);
+        assert_eq!((stats.locked, stats.disputed, stats.total_locked), (0, 0, 0));
+
+        // An offer fill moves tokens that were counted when the offer was funded
+        stats.deposited(50, Some(50)).unwrap();
+        stats.opened_from_offer();
+        assert_eq!((stats.locked, stats.total_escrows, stats.total_locked), (1, 3, 50));
+        stats.returned(50);
+        stats.moved(EscrowStatus::Locked, EscrowStatus::Refunded);
+
+        // Escrows opened before the stats existed settle without underflowing
+        stats.returned(10);
+        stats.moved(EscrowStatus::Locked, EscrowStatus::Refunded);
+        assert_eq!((stats.locked, stats.total_locked), (0, 0));
+    }
+
+    #[test]
+    fn fee_split_rounds_down_and_respects_ceilings() {
+        let fees = |fee_bps, referral_share_bps| ConfigChange::SetFees {
+            fee_bps,
+            referral_share_bps,
+            fee_recipient: Pubkey::new_from_array([5; 32]),
+        };
+        assert!(fees(MAX_FEE_BPS + 1, 0).validate().is_err());
+        assert!(fees(0, MAX_REFERRAL_SHARE_BPS + 1).validate().is_err());
+
+        let mut config = Config {
+            config_admin: Pubkey::new_from_array([4; 32]),
+            pauser: None,
+            arbiter: None,
+            fee_manager: None,
+            mint_manager: None,
+            paused: false,
+            paused_at: 0,
+            bump: 255,
+            config_delay: 0,
+            next_change_id: 0,
+            emergency_grace_period: DEFAULT_EMERGENCY_GRACE_PERIOD,
+            fee_bps: 0,
+            referral_share_bps: 0,
+            fee_recipient: Pubkey::default(),
+            reserved: [0; CONFIG_RESERVED_SPACE],
+        };
+        assert_eq!(config.fee_split(1_000_000), (0, 0));
+
+        config.apply(&fees(50, 2_000)).unwrap();
+        assert_eq!(config.fee_split(1_000_000), (5_000, 1_000));
+        assert_eq!(config.fee_split(199), (0, 0));
+    }
+
+    #[test]
+    fn kyc_attestations_lapse_with_their_attestor() {
+        let issuer = Pubkey::new_from_array([6; 32]);
+        let kyc = KycAttestation {
+            wallet: Pubkey::new_from_array([1; 32]),
+            attestor: issuer,
+            level: 2,
+            issued_at: 1_000,
+            expires_at: 2_000,
+            bump: 255,
+        };
+        let registered = |authority, registered_at| KycAttestor {
+            authority,
+            registered_at,
+            bump: 255,
+        };
+        assert!(kyc.is_valid(2, Some(&registered(issuer, 500)), 1_500));
+        assert!(!kyc.is_valid(3, Some(&registered(issuer, 500)), 1_500));
+        assert!(!kyc.is_valid(2, Some(&registered(issuer, 500)), 2_000));
+
+        // Removed, registered under another key, or re-registered after issuing
+        assert!(!kyc.is_current(None, 1_500));
+        assert!(!kyc.is_current(Some(&registered(Pubkey::new_from_array([7; 32]), 500)), 1_500));
+        assert!(!kyc.is_current(Some(&registered(issuer, 1_200)), 1_500));
+    }
 }
@@ -3,7 +3,7 @@
  *
  * Tests:
  * 1. Happy path: init → mark_paid → release (tokens go to buyer)
- * 2. Dispute flow: init → dispute → admin resolves to buyer
+ * 2. Dispute flow: init → dispute → arbiter resolves to buyer
  * 3. Refund flow: init (short timeout) → wait → seller self-refunds
  */
 
@@ -157,7 +157,7 @@ async function testHappyPath(
   console.log(`  Vault balance: ${vaultAccount.amount}`);
 
   // Buyer marks paid
-  const markPaidIx = buildMarkPaidIx({ buyer: buyer.publicKey, escrowState });
+  const markPaidIx = buildMarkPaidIx({ buyer: buyer.publicKey, escrowState, mint });
   const markPaidTx = new Transaction().add(markPaidIx);
   const markPaidSig = await sendAndConfirmTransaction(connection, markPaidTx, [buyer]);
   console.log(`  Mark paid tx: ${markPaidSig.slice(0, 32)}...`);
@@ -196,7 +196,7 @@ async function testHappyPath(
 }
 
 // ---------------------------------------------------------------------------
-// Test 2: Dispute Flow (init → dispute → admin resolves to buyer)
+// Test 2: Dispute Flow (init → dispute → arbiter resolves to buyer)
 // ---------------------------------------------------------------------------
 
 async function testDisputeResolveToBuyer(
@@ -222,25 +222,25 @@ async function testDisputeResolveToBuyer(
   console.log(`  Init tx: ${initSig.slice(0, 32)}...`);
 
   // Buyer marks paid
-  const markPaidIx = buildMarkPaidIx({ buyer: buyer.publicKey, escrowState });
+  const markPaidIx = buildMarkPaidIx({ buyer: buyer.publicKey, escrowState, mint });
   const markPaidTx = new Transaction().add(markPaidIx);
   await sendAndConfirmTransaction(connection, markPaidTx, [buyer]);
   console.log('  Buyer marked paid');
 
   // Buyer files dispute (seller not confirming)
-  const disputeIx = buildFileDisputeIx({ disputer: buyer.publicKey, escrowState });
+  const disputeIx = buildFileDisputeIx({ disputer: buyer.publicKey, escrowState, mint });
   const disputeTx = new Transaction().add(disputeIx);
   const disputeSig = await sendAndConfirmTransaction(connection, disputeTx, [buyer]);
   console.log(`  Dispute filed tx: ${disputeSig.slice(0, 32)}...`);
 
-  // Admin resolves in favor of buyer
+  // Arbiter resolves in favor of buyer
   const resolveIx = buildResolveDisputeIx({
-    admin: deployer.publicKey,
+    arbiter: deployer.publicKey,
     seller: seller.publicKey,
     buyer: buyer.publicKey,
     mint,
     escrowState,
-    resolution: Resolution.ReleaseToBuyer,
+    resolution: Resolution.ReleaseToBeneficiary,
   });
   const resolveTx = new Transaction().add(resolveIx);
   const resolveSig = await sendAndConfirmTransaction(connection, resolveTx, [deployer]);
@@ -295,7 +295,7 @@ async function testDisputeResolveToSeller(
   console.log(`  Init tx: ${initSig.slice(0, 32)}...`);
 
   // Seller files dispute (buyer hasn't paid)
-  const disputeIx = buildFileDisputeIx({ disputer: seller.publicKey, escrowState });
+  const disputeIx = buildFileDisputeIx({ disputer: seller.publicKey, escrowState, mint });
   const disputeTx = new Transaction().add(disputeIx);
   const disputeSig = await sendAndConfirmTransaction(connection, disputeTx, [seller]);
   console.log(`  Dispute filed tx: ${disputeSig.slice(0, 32)}...`);
@@ -310,14 +310,14 @@ async function testDisputeResolveToSeller(
   const sellerBefore = (await getAccount(connection, sellerAta.address)).amount;
   console.log(`  Seller balance before resolve: ${sellerBefore}`);
 
-  // Admin resolves in favor of seller
+  // Arbiter resolves in favor of seller
   const resolveIx = buildResolveDisputeIx({
-    admin: deployer.publicKey,
+    arbiter: deployer.publicKey,
     seller: seller.publicKey,
     buyer: buyer.publicKey,
     mint,
     escrowState,
-    resolution: Resolution.RefundToSeller,
+    resolution: Resolution.RefundToDepositor,
   });
   const resolveTx = new Transaction().add(resolveIx);
   const resolveSig = await sendAndConfirmTransaction(connection, resolveTx, [deployer]);
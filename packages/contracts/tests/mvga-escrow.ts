@@ -5,10 +5,19 @@ import {
   mintTo,
   getAssociatedTokenAddress,
   createAssociatedTokenAccount,
+  createAccount,
   getAccount,
+  approve,
 } from '@solana/spl-token';
-import { Keypair, PublicKey, LAMPORTS_PER_SOL } from '@solana/web3.js';
+import {
+  Keypair,
+  PublicKey,
+  LAMPORTS_PER_SOL,
+  Ed25519Program,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from '@solana/web3.js';
 import { expect } from 'chai';
+import { createHash, randomBytes } from 'crypto';
 import { MvgaEscrow } from '../target/types/mvga_escrow';
 
 describe('mvga-escrow', () => {
@@ -27,6 +36,8 @@ describe('mvga-escrow', () => {
   const DECIMALS = 6; // USDC-like
   const AMOUNT = 1_000_000; // 1 USDC
   const TIMEOUT = 7200; // 2 hours
+  const SELLER_FUNDED = { sellerFunded: {} };
+  const STANDARD = { standard: {} };
 
   function makeTradeId(): number[] {
     return Array.from({ length: 16 }, () => Math.floor(Math.random() * 256));
@@ -46,6 +57,30 @@ describe('mvga-escrow', () => {
     );
   }
 
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], program.programId);
+
+  async function propose(change: any): Promise<PublicKey> {
+    const { nextChangeId } = await program.account.config.fetch(config);
+    const [pendingChange] = PublicKey.findProgramAddressSync(
+      [Buffer.from('config_change'), nextChangeId.toArrayLike(Buffer, 'le', 8)],
+      program.programId
+    );
+    await program.methods
+      .proposeConfigChange(change)
+      .accounts({ configAdmin: admin.publicKey, pendingChange })
+      .signers([admin])
+      .rpc();
+    return pendingChange;
+  }
+
+  function execute(pendingChange: PublicKey) {
+    return program.methods
+      .executeConfigChange()
+      .accounts({ configAdmin: admin.publicKey, pendingChange })
+      .signers([admin])
+      .rpc();
+  }
+
   before(async () => {
     seller = Keypair.generate();
     buyer = Keypair.generate();
@@ -74,8 +109,23 @@ describe('mvga-escrow', () => {
       buyer.publicKey
     );
 
-    // Mint tokens to seller
+    // Mint tokens to seller and buyer
     await mintTo(provider.connection, seller, mint, sellerAta, seller, 100 * AMOUNT);
+    await mintTo(provider.connection, seller, mint, buyerAta, seller, 10 * AMOUNT);
+
+    // Program config: admin keeps the config admin role and also arbitrates and manages fees
+    await program.methods
+      .initializeConfig(admin.publicKey)
+      .accounts({ admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    for (const role of [{ arbiter: {} }, { feeManager: {} }]) {
+      await program.methods
+        .grantRole(role, admin.publicKey)
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    }
   });
 
   describe('initialize_escrow', () => {
@@ -85,23 +135,36 @@ describe('mvga-escrow', () => {
       const [vault] = findVaultPDA(escrowState);
 
       await program.methods
-        .initializeEscrow(tradeId, new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           admin: admin.publicKey,
           mint,
           escrowState,
           vault,
-          sellerTokenAccount: sellerAta,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
         })
         .signers([seller])
         .rpc();
 
       // Verify escrow state
       const escrow = await program.account.escrowState.fetch(escrowState);
-      expect(escrow.seller.toBase58()).to.equal(seller.publicKey.toBase58());
-      expect(escrow.buyer.toBase58()).to.equal(buyer.publicKey.toBase58());
+      expect(escrow.depositor.toBase58()).to.equal(seller.publicKey.toBase58());
+      expect(escrow.beneficiary.toBase58()).to.equal(buyer.publicKey.toBase58());
       expect(escrow.amount.toNumber()).to.equal(AMOUNT);
       expect(escrow.status).to.deep.equal({ locked: {} });
       expect(escrow.timeoutSeconds.toNumber()).to.equal(TIMEOUT);
@@ -111,6 +174,44 @@ describe('mvga-escrow', () => {
       expect(Number(vaultAccount.amount)).to.equal(AMOUNT);
     });
 
+    it('lets the buyer fund a buyer-funded escrow', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, buyer.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          { buyerFunded: {} },
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: buyer.publicKey,
+          rentPayer: buyer.publicKey,
+          beneficiary: seller.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: buyerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.depositor.toBase58()).to.equal(buyer.publicKey.toBase58());
+      expect(escrow.beneficiary.toBase58()).to.equal(seller.publicKey.toBase58());
+      expect(escrow.direction).to.deep.equal({ buyerFunded: {} });
+    });
+
     it('rejects zero amount', async () => {
       const tradeId = makeTradeId();
       const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
@@ -118,15 +219,28 @@ describe('mvga-escrow', () => {
 
       try {
         await program.methods
-          .initializeEscrow(tradeId, new anchor.BN(0), new anchor.BN(TIMEOUT))
+          .initializeEscrow(
+            tradeId,
+            new anchor.BN(0),
+            new anchor.BN(TIMEOUT),
+            SELLER_FUNDED,
+            STANDARD,
+            false,
+            null
+          )
           .accounts({
-            seller: seller.publicKey,
-            buyer: buyer.publicKey,
+            depositor: seller.publicKey,
+            rentPayer: seller.publicKey,
+            beneficiary: buyer.publicKey,
             admin: admin.publicKey,
             mint,
             escrowState,
             vault,
-            sellerTokenAccount: sellerAta,
+            depositorTokenAccount: sellerAta,
+            rentSponsor: null,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
           })
           .signers([seller])
           .rpc();
@@ -147,15 +261,28 @@ describe('mvga-escrow', () => {
       const [vault] = findVaultPDA(escrowState);
 
       await program.methods
-        .initializeEscrow(tradeId, new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           admin: admin.publicKey,
           mint,
           escrowState,
           vault,
-          sellerTokenAccount: sellerAta,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
         })
         .signers([seller])
         .rpc();
@@ -163,8 +290,13 @@ describe('mvga-escrow', () => {
 
     it('buyer marks payment as sent', async () => {
       await program.methods
-        .markPaid()
-        .accounts({ buyer: buyer.publicKey, escrowState })
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
         .signers([buyer])
         .rpc();
 
@@ -178,28 +310,46 @@ describe('mvga-escrow', () => {
       const [v2] = findVaultPDA(es2);
 
       await program.methods
-        .initializeEscrow(tradeId2, new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+        .initializeEscrow(
+          tradeId2,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           admin: admin.publicKey,
           mint,
           escrowState: es2,
           vault: v2,
-          sellerTokenAccount: sellerAta,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
         })
         .signers([seller])
         .rpc();
 
       try {
         await program.methods
-          .markPaid()
-          .accounts({ buyer: seller.publicKey, escrowState: es2 })
+          .markPaid(null)
+          .accounts({
+            authority: seller.publicKey,
+            escrowState: es2,
+            sessionKey: null,
+            payoutTokenAccount: null,
+          })
           .signers([seller])
           .rpc();
         expect.fail('Should have thrown');
       } catch (err: any) {
-        expect(err.error.errorCode.code).to.equal('UnauthorizedBuyer');
+        expect(err.error.errorCode.code).to.equal('UnauthorizedBeneficiary');
       }
     });
   });
@@ -212,23 +362,41 @@ describe('mvga-escrow', () => {
 
       // Initialize
       await program.methods
-        .initializeEscrow(tradeId, new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           admin: admin.publicKey,
           mint,
           escrowState,
           vault,
-          sellerTokenAccount: sellerAta,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
         })
         .signers([seller])
         .rpc();
 
       // Mark paid
       await program.methods
-        .markPaid()
-        .accounts({ buyer: buyer.publicKey, escrowState })
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
         .signers([buyer])
         .rpc();
 
@@ -240,12 +408,16 @@ describe('mvga-escrow', () => {
       await program.methods
         .releaseEscrow()
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          authority: seller.publicKey,
+          payer: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           mint,
           escrowState,
           vault,
-          buyerTokenAccount: buyerAta,
+          beneficiaryTokenAccount: buyerAta,
+          payoutTokenAccount: null,
+          sessionKey: null,
         })
         .signers([seller])
         .rpc();
@@ -258,6 +430,197 @@ describe('mvga-escrow', () => {
       const escrow = await program.account.escrowState.fetch(escrowState);
       expect(escrow.status).to.deep.equal({ released: {} });
     });
+
+    it('releases milestones one at a time and keeps the vault open', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const first = AMOUNT / 4;
+      const second = AMOUNT - first;
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          {
+            milestones: {
+              milestones: [
+                { amount: new anchor.BN(first), deadline: null },
+                { amount: new anchor.BN(second), deadline: null },
+              ],
+            },
+          },
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const releaseAccounts = {
+        authority: seller.publicKey,
+        payer: seller.publicKey,
+        rentPayer: seller.publicKey,
+        beneficiary: buyer.publicKey,
+        mint,
+        escrowState,
+        vault,
+        beneficiaryTokenAccount: buyerAta,
+        payoutTokenAccount: null,
+        sessionKey: null,
+      };
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+      await program.methods.releaseEscrow().accounts(releaseAccounts).signers([seller]).rpc();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.amount.toNumber()).to.equal(second);
+      expect(escrow.nextMilestone).to.equal(1);
+      expect(escrow.status).to.deep.equal({ locked: {} });
+      const vaultAccount = await getAccount(provider.connection, vault);
+      expect(Number(vaultAccount.amount)).to.equal(second);
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+      await program.methods.releaseEscrow().accounts(releaseAccounts).signers([seller]).rpc();
+
+      expect(await provider.connection.getAccountInfo(escrowState)).to.be.null;
+      expect(await provider.connection.getAccountInfo(vault)).to.be.null;
+    });
+
+    it('releases with a seller-signed voucher submitted by the buyer', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      // Seller signs off-chain — no transaction, no SOL needed
+      const { createdAt } = await program.account.escrowState.fetch(escrowState);
+      const voucher = {
+        tradeId,
+        escrow: escrowState,
+        createdAt,
+        amount: new anchor.BN(AMOUNT),
+        expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+      };
+      const signVoucher = (signed: typeof voucher) =>
+        Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: seller.secretKey,
+          message: Buffer.concat([
+            Buffer.from('mvga-escrow:release:v2'),
+            program.programId.toBuffer(),
+            program.coder.types.encode('releaseVoucher', signed),
+          ]),
+        });
+      const ed25519Ix = signVoucher(voucher);
+      const releaseAccounts = {
+        submitter: buyer.publicKey,
+        rentPayer: seller.publicKey,
+        beneficiary: buyer.publicKey,
+        mint,
+        escrowState,
+        vault,
+        beneficiaryTokenAccount: buyerAta,
+        payoutTokenAccount: null,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      };
+
+      // A voucher signed for an earlier escrow at the same PDA doesn't carry over
+      const staleVoucher = { ...voucher, createdAt: createdAt.subn(60) };
+      try {
+        await program.methods
+          .releaseWithVoucher(staleVoucher)
+          .accounts(releaseAccounts)
+          .preInstructions([signVoucher(staleVoucher)])
+          .signers([buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('VoucherMismatch');
+      }
+
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+
+      await program.methods
+        .releaseWithVoucher(voucher)
+        .accounts(releaseAccounts)
+        .preInstructions([ed25519Ix])
+        .signers([buyer])
+        .rpc();
+
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      expect(buyerAfter - buyerBefore).to.equal(AMOUNT);
+      expect(await provider.connection.getAccountInfo(escrowState)).to.be.null;
+    });
   });
 
   describe('file_dispute', () => {
@@ -267,22 +630,35 @@ describe('mvga-escrow', () => {
       const [vault] = findVaultPDA(escrowState);
 
       await program.methods
-        .initializeEscrow(tradeId, new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           admin: admin.publicKey,
           mint,
           escrowState,
           vault,
-          sellerTokenAccount: sellerAta,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
         })
         .signers([seller])
         .rpc();
 
       await program.methods
         .fileDispute()
-        .accounts({ disputer: buyer.publicKey, escrowState })
+        .accounts({ disputer: buyer.publicKey, escrowState, sessionKey: null })
         .signers([buyer])
         .rpc();
 
@@ -298,15 +674,28 @@ describe('mvga-escrow', () => {
       const [vault] = findVaultPDA(escrowState);
 
       await program.methods
-        .initializeEscrow(tradeId, new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           admin: admin.publicKey,
           mint,
           escrowState,
           vault,
-          sellerTokenAccount: sellerAta,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
         })
         .signers([seller])
         .rpc();
@@ -314,24 +703,25 @@ describe('mvga-escrow', () => {
       // File dispute
       await program.methods
         .fileDispute()
-        .accounts({ disputer: seller.publicKey, escrowState })
+        .accounts({ disputer: seller.publicKey, escrowState, sessionKey: null })
         .signers([seller])
         .rpc();
 
       const buyerBefore = await getAccount(provider.connection, buyerAta);
       const beforeBalance = Number(buyerBefore.amount);
 
-      // Admin resolves → release to buyer
+      // Arbiter resolves → release to buyer
       await program.methods
-        .resolveDispute({ releaseToBuyer: {} })
+        .resolveDispute({ releaseToBeneficiary: {} })
         .accounts({
-          admin: admin.publicKey,
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          arbiter: admin.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           mint,
           escrowState,
           vault,
           recipientTokenAccount: buyerAta,
+          destinationTokenAccount: null,
           recipient: buyer.publicKey,
         })
         .signers([admin])
@@ -350,39 +740,53 @@ describe('mvga-escrow', () => {
       const [vault] = findVaultPDA(escrowState);
 
       await program.methods
-        .initializeEscrow(tradeId, new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
         .accounts({
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           admin: admin.publicKey,
           mint,
           escrowState,
           vault,
-          sellerTokenAccount: sellerAta,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
         })
         .signers([seller])
         .rpc();
 
       await program.methods
         .fileDispute()
-        .accounts({ disputer: buyer.publicKey, escrowState })
+        .accounts({ disputer: buyer.publicKey, escrowState, sessionKey: null })
         .signers([buyer])
         .rpc();
 
       const sellerBefore = await getAccount(provider.connection, sellerAta);
       const beforeBalance = Number(sellerBefore.amount);
 
-      // Admin resolves → refund to seller
+      // Arbiter resolves → refund to seller
       await program.methods
-        .resolveDispute({ refundToSeller: {} })
+        .resolveDispute({ refundToDepositor: {} })
         .accounts({
-          admin: admin.publicKey,
-          seller: seller.publicKey,
-          buyer: buyer.publicKey,
+          arbiter: admin.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
           mint,
           escrowState,
           vault,
           recipientTokenAccount: sellerAta,
+          destinationTokenAccount: null,
           recipient: seller.publicKey,
         })
         .signers([admin])
@@ -395,4 +799,2562 @@ describe('mvga-escrow', () => {
       expect(escrow.status).to.deep.equal({ refunded: {} });
     });
   });
+
+  describe('vesting', () => {
+    it('streams tokens to the beneficiary and refunds the rest on cancel', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const now = Math.floor(Date.now() / 1000);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          {
+            vesting: { start: new anchor.BN(now - 3600), end: new anchor.BN(now + 3600) },
+          },
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+      await program.methods
+        .claimVested()
+        .accounts({
+          beneficiary: buyer.publicKey,
+          rentPayer: seller.publicKey,
+          mint,
+          escrowState,
+          vault,
+          beneficiaryTokenAccount: buyerAta,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const afterClaim = Number((await getAccount(provider.connection, buyerAta)).amount);
+      const claimed = afterClaim - buyerBefore;
+      expect(claimed).to.be.greaterThan(0);
+      expect(claimed).to.be.lessThan(AMOUNT);
+
+      const sellerBefore = Number((await getAccount(provider.connection, sellerAta)).amount);
+      await program.methods
+        .cancelVesting()
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          mint,
+          escrowState,
+          vault,
+          beneficiaryTokenAccount: buyerAta,
+          payoutTokenAccount: null,
+          depositorTokenAccount: sellerAta,
+          refundTokenAccount: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      const sellerAfter = Number((await getAccount(provider.connection, sellerAta)).amount);
+      expect(buyerAfter - buyerBefore + (sellerAfter - sellerBefore)).to.equal(AMOUNT);
+      expect(await provider.connection.getAccountInfo(escrowState)).to.be.null;
+    });
+  });
+
+  describe('hashlock', () => {
+    it('releases to the beneficiary when anyone reveals the preimage', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const preimage = randomBytes(32);
+      const hash = Array.from(createHash('sha256').update(preimage).digest());
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          {
+            hashlock: { algorithm: { sha256: {} }, hash },
+          },
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const claimAccounts = {
+        submitter: admin.publicKey,
+        beneficiary: buyer.publicKey,
+        rentPayer: seller.publicKey,
+        mint,
+        escrowState,
+        vault,
+        beneficiaryTokenAccount: buyerAta,
+        payoutTokenAccount: null,
+      };
+
+      try {
+        await program.methods
+          .claimWithPreimage(randomBytes(32))
+          .accounts(claimAccounts)
+          .signers([admin])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('InvalidPreimage');
+      }
+
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+      await program.methods
+        .claimWithPreimage(preimage)
+        .accounts(claimAccounts)
+        .signers([admin])
+        .rpc();
+
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      expect(buyerAfter - buyerBefore).to.equal(AMOUNT);
+      expect(await provider.connection.getAccountInfo(escrowState)).to.be.null;
+    });
+
+    it('rejects a preimage revealed after the timeout', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const preimage = randomBytes(32);
+      const hash = Array.from(createHash('sha256').update(preimage).digest());
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(1),
+          SELLER_FUNDED,
+          {
+            hashlock: { algorithm: { sha256: {} }, hash },
+          },
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+      try {
+        await program.methods
+          .claimWithPreimage(preimage)
+          .accounts({
+            submitter: admin.publicKey,
+            beneficiary: buyer.publicKey,
+            rentPayer: seller.publicKey,
+            mint,
+            escrowState,
+            vault,
+            beneficiaryTokenAccount: buyerAta,
+            payoutTokenAccount: null,
+          })
+          .signers([admin])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('HashlockExpired');
+      }
+    });
+  });
+
+  describe('swap', () => {
+    it('settles both legs atomically when the taker arrives', async () => {
+      const mintB = await createMint(provider.connection, buyer, buyer.publicKey, null, DECIMALS);
+      const buyerAtaB = await createAssociatedTokenAccount(
+        provider.connection,
+        buyer,
+        mintB,
+        buyer.publicKey
+      );
+      await mintTo(provider.connection, buyer, mintB, buyerAtaB, buyer, 10 * AMOUNT);
+
+      const swapId = makeTradeId();
+      const [swapState] = PublicKey.findProgramAddressSync(
+        [Buffer.from('swap'), Buffer.from(swapId), seller.publicKey.toBuffer()],
+        program.programId
+      );
+      const [vault] = findVaultPDA(swapState);
+
+      await program.methods
+        .initializeSwap(swapId, new anchor.BN(AMOUNT), new anchor.BN(2 * AMOUNT), buyer.publicKey)
+        .accounts({
+          maker: seller.publicKey,
+          mintA: mint,
+          mintB,
+          swapState,
+          vault,
+          makerTokenAccountA: sellerAta,
+        })
+        .signers([seller])
+        .rpc();
+
+      const sellerAtaB = await getAssociatedTokenAddress(mintB, seller.publicKey);
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+
+      await program.methods
+        .takeSwap()
+        .accounts({
+          taker: buyer.publicKey,
+          maker: seller.publicKey,
+          mintA: mint,
+          mintB,
+          swapState,
+          vault,
+          takerTokenAccountA: buyerAta,
+          takerTokenAccountB: buyerAtaB,
+          makerTokenAccountB: sellerAtaB,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      expect(buyerAfter - buyerBefore).to.equal(AMOUNT);
+      const sellerB = await getAccount(provider.connection, sellerAtaB);
+      expect(Number(sellerB.amount)).to.equal(2 * AMOUNT);
+      expect(await provider.connection.getAccountInfo(swapState)).to.be.null;
+    });
+  });
+
+  describe('offers', () => {
+    it('fills an offer partially into a child escrow and returns the rest on close', async () => {
+      const offerId = makeTradeId();
+      const [offer] = PublicKey.findProgramAddressSync(
+        [Buffer.from('offer'), Buffer.from(offerId), seller.publicKey.toBuffer()],
+        program.programId
+      );
+      const [offerVault] = findVaultPDA(offer);
+
+      await program.methods
+        .createOffer(
+          offerId,
+          new anchor.BN(5 * AMOUNT),
+          new anchor.BN(AMOUNT),
+          new anchor.BN(2 * AMOUNT),
+          0b11,
+          new anchor.BN(36_500_000),
+          Array.from(Buffer.from('VES')),
+          new anchor.BN(TIMEOUT)
+        )
+        .accounts({
+          seller: seller.publicKey,
+          admin: admin.publicKey,
+          mint,
+          offer,
+          offerVault,
+          sellerTokenAccount: sellerAta,
+        })
+        .signers([seller])
+        .rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .takeOffer(tradeId, new anchor.BN(2 * AMOUNT), 1)
+        .accounts({
+          buyer: buyer.publicKey,
+          mint,
+          offer,
+          offerVault,
+          escrowState,
+          vault,
+          payoutTokenAccount: null,
+          sellerKyc: null,
+          buyerKyc: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.depositor.toBase58()).to.equal(seller.publicKey.toBase58());
+      expect(escrow.beneficiary.toBase58()).to.equal(buyer.publicKey.toBase58());
+      expect(escrow.amount.toNumber()).to.equal(2 * AMOUNT);
+      const offerAccount = await program.account.offer.fetch(offer);
+      expect(offerAccount.available.toNumber()).to.equal(3 * AMOUNT);
+      expect(offerAccount.fillCount.toNumber()).to.equal(1);
+
+      const sellerBefore = Number((await getAccount(provider.connection, sellerAta)).amount);
+
+      await program.methods
+        .closeOffer()
+        .accounts({
+          seller: seller.publicKey,
+          mint,
+          offer,
+          offerVault,
+          sellerTokenAccount: sellerAta,
+        })
+        .signers([seller])
+        .rpc();
+
+      const sellerAfter = Number((await getAccount(provider.connection, sellerAta)).amount);
+      expect(sellerAfter - sellerBefore).to.equal(3 * AMOUNT);
+      expect(await provider.connection.getAccountInfo(offer)).to.be.null;
+      // The child escrow is unaffected by closing the offer
+      const vaultAccount = await getAccount(provider.connection, vault);
+      expect(Number(vaultAccount.amount)).to.equal(2 * AMOUNT);
+    });
+  });
+
+  describe('signed orders', () => {
+    it('creates an escrow from a seller-signed order and rejects replays', async () => {
+      const order = {
+        seller: seller.publicKey,
+        mint,
+        minAmount: new anchor.BN(AMOUNT),
+        maxAmount: new anchor.BN(3 * AMOUNT),
+        price: new anchor.BN(36_500_000),
+        fiatCurrency: Array.from(Buffer.from('VES')),
+        timeoutSeconds: new anchor.BN(TIMEOUT),
+        expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        nonce: new anchor.BN(Date.now()),
+      };
+      const message = Buffer.concat([
+        Buffer.from('mvga-escrow:order:v1'),
+        program.programId.toBuffer(),
+        program.coder.types.encode('signedOrder', order),
+      ]);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: seller.secretKey,
+        message,
+      });
+
+      const [orderAuthority] = PublicKey.findProgramAddressSync(
+        [Buffer.from('order_authority')],
+        program.programId
+      );
+      await approve(provider.connection, seller, sellerAta, orderAuthority, seller, 3 * AMOUNT);
+
+      const [orderNonce] = PublicKey.findProgramAddressSync(
+        [Buffer.from('order_nonce'), seller.publicKey.toBuffer(), order.nonce.toBuffer('le', 8)],
+        program.programId
+      );
+
+      const fill = (tradeId: number[]) => {
+        const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+        const [vault] = findVaultPDA(escrowState);
+        return program.methods
+          .fillSignedOrder(order, tradeId, new anchor.BN(2 * AMOUNT))
+          .accounts({
+            buyer: buyer.publicKey,
+            admin: admin.publicKey,
+            mint,
+            orderAuthority,
+            orderNonce,
+            escrowState,
+            vault,
+            sellerTokenAccount: sellerAta,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            payoutTokenAccount: null,
+            sellerKyc: null,
+            buyerKyc: null,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([buyer])
+          .rpc();
+      };
+
+      const tradeId = makeTradeId();
+      await fill(tradeId);
+
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.depositor.toBase58()).to.equal(seller.publicKey.toBase58());
+      expect(escrow.beneficiary.toBase58()).to.equal(buyer.publicKey.toBase58());
+      expect(escrow.amount.toNumber()).to.equal(2 * AMOUNT);
+
+      try {
+        await fill(makeTradeId());
+        expect.fail('Should have thrown');
+      } catch (err) {
+        expect(err.toString()).to.include('already in use');
+      }
+    });
+  });
+
+  describe('attested release', () => {
+    it('releases on a registered attestor confirmation when the seller opted in', async () => {
+      const attestorKey = Keypair.generate();
+      const [attestor] = PublicKey.findProgramAddressSync(
+        [Buffer.from('attestor'), attestorKey.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .registerAttestor(attestorKey.publicKey)
+        .accounts({ configAdmin: admin.publicKey, attestor })
+        .signers([admin])
+        .rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          true,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      // Backend confirms the fiat leg and signs off-chain
+      const { createdAt } = await program.account.escrowState.fetch(escrowState);
+      const attestation = {
+        tradeId,
+        escrow: escrowState,
+        createdAt,
+        milestone: 0,
+        amount: new anchor.BN(AMOUNT),
+      };
+      const message = Buffer.concat([
+        Buffer.from('mvga-escrow:attestation:v2'),
+        program.programId.toBuffer(),
+        program.coder.types.encode('paymentAttestation', attestation),
+      ]);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: attestorKey.secretKey,
+        message,
+      });
+
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+
+      await program.methods
+        .releaseWithAttestation(attestation)
+        .accounts({
+          submitter: buyer.publicKey,
+          attestor,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          mint,
+          escrowState,
+          vault,
+          beneficiaryTokenAccount: buyerAta,
+          payoutTokenAccount: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([buyer])
+        .rpc();
+
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      expect(buyerAfter - buyerBefore).to.equal(AMOUNT);
+      expect(await provider.connection.getAccountInfo(escrowState)).to.be.null;
+    });
+  });
+
+  describe('session keys', () => {
+    it('lets hot keys mark paid and release within the spend cap', async () => {
+      const buyerHot = Keypair.generate();
+      const sellerHot = Keypair.generate();
+      for (const kp of [buyerHot, sellerHot]) {
+        const sig = await provider.connection.requestAirdrop(kp.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(sig);
+      }
+
+      const findSessionPDA = (owner: PublicKey, delegate: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from('session'), owner.toBuffer(), delegate.toBuffer()],
+          program.programId
+        )[0];
+      const buyerSession = findSessionPDA(buyer.publicKey, buyerHot.publicKey);
+      const sellerSession = findSessionPDA(seller.publicKey, sellerHot.publicKey);
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+
+      await program.methods
+        .createSessionKey(buyerHot.publicKey, 0b001, expiresAt, new anchor.BN(0))
+        .accounts({ owner: buyer.publicKey, sessionKey: buyerSession })
+        .signers([buyer])
+        .rpc();
+      await program.methods
+        .createSessionKey(sellerHot.publicKey, 0b010, expiresAt, new anchor.BN(AMOUNT))
+        .accounts({ owner: seller.publicKey, sessionKey: sellerSession })
+        .signers([seller])
+        .rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      // The hot key can mark paid but not redirect where the release goes
+      try {
+        await program.methods
+          .markPaid(null)
+          .accounts({
+            authority: buyerHot.publicKey,
+            escrowState,
+            sessionKey: buyerSession,
+            payoutTokenAccount: sellerAta,
+          })
+          .signers([buyerHot])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('PayoutChangeNotAllowed');
+      }
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyerHot.publicKey,
+          escrowState,
+          sessionKey: buyerSession,
+          payoutTokenAccount: null,
+        })
+        .signers([buyerHot])
+        .rpc();
+
+      await program.methods
+        .releaseEscrow()
+        .accounts({
+          authority: sellerHot.publicKey,
+          payer: sellerHot.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          mint,
+          escrowState,
+          vault,
+          beneficiaryTokenAccount: buyerAta,
+          payoutTokenAccount: null,
+          sessionKey: sellerSession,
+        })
+        .signers([sellerHot])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(escrowState)).to.be.null;
+      const session = await program.account.sessionKey.fetch(sellerSession);
+      expect(session.spent.toNumber()).to.equal(AMOUNT);
+
+      await program.methods
+        .revokeSessionKey()
+        .accounts({ owner: seller.publicKey, sessionKey: sellerSession })
+        .signers([seller])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(sellerSession)).to.be.null;
+    });
+  });
+
+  describe('rent sponsor', () => {
+    it('fronts rent for a token-only seller and gets it back on release', async () => {
+      const [rentSponsor] = PublicKey.findProgramAddressSync(
+        [Buffer.from('rent_sponsor')],
+        program.programId
+      );
+
+      await program.methods
+        .configureRentSponsor(admin.publicKey)
+        .accounts({ feeManager: admin.publicKey, rentSponsor })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .fundRentSponsor(new anchor.BN(LAMPORTS_PER_SOL))
+        .accounts({ funder: provider.wallet.publicKey, rentSponsor })
+        .rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const poolBefore = await provider.connection.getBalance(rentSponsor);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: admin.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor,
+        })
+        .signers([seller, admin])
+        .rpc();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.rentPayer.toBase58()).to.equal(rentSponsor.toBase58());
+      expect(await provider.connection.getBalance(rentSponsor)).to.be.lessThan(poolBefore);
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+      await program.methods
+        .releaseEscrow()
+        .accounts({
+          authority: seller.publicKey,
+          payer: buyer.publicKey,
+          rentPayer: rentSponsor,
+          beneficiary: buyer.publicKey,
+          mint,
+          escrowState,
+          vault,
+          beneficiaryTokenAccount: buyerAta,
+          payoutTokenAccount: null,
+          sessionKey: null,
+        })
+        .signers([seller, buyer])
+        .rpc();
+
+      expect(await provider.connection.getBalance(rentSponsor)).to.equal(poolBefore);
+      const sponsor = await program.account.rentSponsor.fetch(rentSponsor);
+      expect(sponsor.totalFronted.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe('payout destinations', () => {
+    it('pays the account the buyer chose at mark_paid instead of the ATA', async () => {
+      const payoutAccount = await createAccount(
+        provider.connection,
+        buyer,
+        mint,
+        buyer.publicKey,
+        Keypair.generate()
+      );
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: payoutAccount,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.payoutAccount.toBase58()).to.equal(payoutAccount.toBase58());
+
+      const releaseAccounts = {
+        authority: seller.publicKey,
+        payer: seller.publicKey,
+        rentPayer: seller.publicKey,
+        beneficiary: buyer.publicKey,
+        mint,
+        escrowState,
+        vault,
+        beneficiaryTokenAccount: buyerAta,
+        payoutTokenAccount: null,
+        sessionKey: null,
+      };
+
+      try {
+        await program.methods.releaseEscrow().accounts(releaseAccounts).signers([seller]).rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('InvalidDestination');
+      }
+
+      await program.methods
+        .releaseEscrow()
+        .accounts({
+          ...releaseAccounts,
+          beneficiaryTokenAccount: null,
+          payoutTokenAccount: payoutAccount,
+        })
+        .signers([seller])
+        .rpc();
+
+      const payout = await getAccount(provider.connection, payoutAccount);
+      expect(Number(payout.amount)).to.equal(AMOUNT);
+    });
+  });
+
+  describe('amend_escrow', () => {
+    it('tops up and reduces the amount with both signatures', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const amendAccounts = {
+        depositor: seller.publicKey,
+        beneficiary: buyer.publicKey,
+        mint,
+        escrowState,
+        vault,
+        depositorTokenAccount: sellerAta,
+        refundTokenAccount: null,
+        depositorKyc: null,
+        beneficiaryKyc: null,
+      };
+
+      await program.methods
+        .amendEscrow(new anchor.BN(3 * AMOUNT), new anchor.BN(2 * TIMEOUT))
+        .accounts(amendAccounts)
+        .signers([seller, buyer])
+        .rpc();
+
+      let escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.amount.toNumber()).to.equal(3 * AMOUNT);
+      expect(escrow.timeoutSeconds.toNumber()).to.equal(2 * TIMEOUT);
+      expect(Number((await getAccount(provider.connection, vault)).amount)).to.equal(3 * AMOUNT);
+
+      await program.methods
+        .amendEscrow(new anchor.BN(AMOUNT / 2), null)
+        .accounts(amendAccounts)
+        .signers([seller, buyer])
+        .rpc();
+
+      escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.amount.toNumber()).to.equal(AMOUNT / 2);
+      expect(escrow.timeoutSeconds.toNumber()).to.equal(2 * TIMEOUT);
+      expect(Number((await getAccount(provider.connection, vault)).amount)).to.equal(AMOUNT / 2);
+
+      try {
+        await program.methods
+          .amendEscrow(new anchor.BN(AMOUNT), new anchor.BN(TIMEOUT))
+          .accounts(amendAccounts)
+          .signers([seller, buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TimeoutNotExtended');
+      }
+
+      // A pause stops top-ups, while a reduction still goes through
+      await program.methods.pause().accounts({ pauser: admin.publicKey }).signers([admin]).rpc();
+      try {
+        await program.methods
+          .amendEscrow(new anchor.BN(AMOUNT), null)
+          .accounts(amendAccounts)
+          .signers([seller, buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('ProgramPaused');
+      }
+      await program.methods
+        .amendEscrow(new anchor.BN(AMOUNT / 4), null)
+        .accounts(amendAccounts)
+        .signers([seller, buyer])
+        .rpc();
+      await program.methods
+        .unpause()
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    });
+  });
+
+  describe('extend_timeout', () => {
+    it('pushes back the refund deadline with both signatures, up to 30 days', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const extendAccounts = {
+        depositor: seller.publicKey,
+        beneficiary: buyer.publicKey,
+        escrowState,
+      };
+
+      await program.methods
+        .extendTimeout(new anchor.BN(3 * 24 * 3600))
+        .accounts(extendAccounts)
+        .signers([seller, buyer])
+        .rpc();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.timeoutSeconds.toNumber()).to.equal(3 * 24 * 3600);
+
+      try {
+        await program.methods
+          .extendTimeout(new anchor.BN(31 * 24 * 3600))
+          .accounts(extendAccounts)
+          .signers([seller, buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TimeoutTooLong');
+      }
+
+      // A hashlock's timeout bounds the preimage window, so it can't be stretched
+      const hashTradeId = makeTradeId();
+      const [hashEscrow] = findEscrowPDA(hashTradeId, seller.publicKey);
+      const hash = Array.from(createHash('sha256').update(randomBytes(32)).digest());
+      await program.methods
+        .initializeEscrow(
+          hashTradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          { hashlock: { algorithm: { sha256: {} }, hash } },
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState: hashEscrow,
+          vault: findVaultPDA(hashEscrow)[0],
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+      try {
+        await program.methods
+          .extendTimeout(new anchor.BN(3 * 24 * 3600))
+          .accounts({ ...extendAccounts, escrowState: hashEscrow })
+          .signers([seller, buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('InvalidMode');
+      }
+    });
+  });
+
+  describe('trade metadata', () => {
+    it('records the fiat side of the trade at initialization', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const termsHash = Array.from(createHash('sha256').update('trade terms').digest());
+      const metadata = {
+        fiatCurrency: Array.from(Buffer.from('VES')),
+        fiatAmount: new anchor.BN(36_500),
+        price: new anchor.BN(36_500),
+        paymentMethod: { pagoMovil: {} },
+        termsHash,
+      };
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          metadata
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(Buffer.from(escrow.metadata.fiatCurrency).toString()).to.equal('VES');
+      expect(escrow.metadata.fiatAmount.toNumber()).to.equal(36_500);
+      expect(escrow.metadata.paymentMethod).to.deep.equal({ pagoMovil: {} });
+      expect(escrow.metadata.termsHash).to.deep.equal(termsHash);
+    });
+  });
+
+  describe('payment reference', () => {
+    it('stores the reference from mark_paid and allows one update', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const reference = (ref: string, fiatAmount: number) => ({
+        referenceHash: Array.from(createHash('sha256').update(ref).digest()),
+        fiatAmount: new anchor.BN(fiatAmount),
+      });
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      await program.methods
+        .markPaid(reference('PM-0001', 36_500))
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      let escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.paymentReference.fiatAmount.toNumber()).to.equal(36_500);
+
+      const updateAccounts = { authority: buyer.publicKey, escrowState, sessionKey: null };
+      await program.methods
+        .updatePaymentReference(reference('PM-0002', 36_000))
+        .accounts(updateAccounts)
+        .signers([buyer])
+        .rpc();
+
+      escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.paymentReference.fiatAmount.toNumber()).to.equal(36_000);
+      expect(escrow.paymentReferenceUpdated).to.be.true;
+
+      try {
+        await program.methods
+          .updatePaymentReference(reference('PM-0003', 35_000))
+          .accounts(updateAccounts)
+          .signers([buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('PaymentReferenceLocked');
+      }
+    });
+  });
+
+  describe('roles', () => {
+    it('lets the pauser stop new escrows until the config admin unpauses', async () => {
+      const pauser = Keypair.generate();
+      await program.methods
+        .grantRole({ pauser: {} }, pauser.publicKey)
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      await program.methods.pause().accounts({ pauser: pauser.publicKey }).signers([pauser]).rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const init = () =>
+        program.methods
+          .initializeEscrow(
+            tradeId,
+            new anchor.BN(AMOUNT),
+            new anchor.BN(TIMEOUT),
+            SELLER_FUNDED,
+            STANDARD,
+            false,
+            null
+          )
+          .accounts({
+            depositor: seller.publicKey,
+            rentPayer: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            admin: admin.publicKey,
+            mint,
+            escrowState,
+            vault,
+            depositorTokenAccount: sellerAta,
+            rentSponsor: null,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([seller])
+          .rpc();
+
+      try {
+        await init();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('ProgramPaused');
+      }
+
+      // Offers can't take in new liquidity either
+      const offerId = makeTradeId();
+      const [offer] = PublicKey.findProgramAddressSync(
+        [Buffer.from('offer'), Buffer.from(offerId), seller.publicKey.toBuffer()],
+        program.programId
+      );
+      try {
+        await program.methods
+          .createOffer(
+            offerId,
+            new anchor.BN(AMOUNT),
+            new anchor.BN(AMOUNT),
+            new anchor.BN(AMOUNT),
+            0b1,
+            new anchor.BN(36_500_000),
+            Array.from(Buffer.from('VES')),
+            new anchor.BN(TIMEOUT)
+          )
+          .accounts({
+            seller: seller.publicKey,
+            admin: admin.publicKey,
+            mint,
+            offer,
+            offerVault: findVaultPDA(offer)[0],
+            sellerTokenAccount: sellerAta,
+          })
+          .signers([seller])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('ProgramPaused');
+      }
+
+      // The pauser is a hot key — it cannot lift the pause
+      try {
+        await program.methods
+          .unpause()
+          .accounts({ configAdmin: pauser.publicKey })
+          .signers([pauser])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('MissingRole');
+      }
+
+      await program.methods
+        .unpause()
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      await init();
+
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.status).to.deep.equal({ locked: {} });
+    });
+
+    it('only the config admin grants and revokes roles', async () => {
+      const outsider = Keypair.generate();
+      try {
+        await program.methods
+          .grantRole({ pauser: {} }, outsider.publicKey)
+          .accounts({ configAdmin: outsider.publicKey })
+          .signers([outsider])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('MissingRole');
+      }
+
+      await program.methods
+        .revokeRole({ pauser: {} })
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      const state = await program.account.config.fetch(config);
+      expect(state.pauser).to.be.null;
+      expect(state.arbiter.toBase58()).to.equal(admin.publicKey.toBase58());
+
+      try {
+        await program.methods
+          .revokeRole({ configAdmin: {} })
+          .accounts({ configAdmin: admin.publicKey })
+          .signers([admin])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('ConfigAdminNotRevocable');
+      }
+    });
+  });
+
+  describe('config timelock', () => {
+    it('queues role changes behind the config delay', async () => {
+      // With no delay yet, setting one executes straight away
+      await execute(await propose({ setConfigDelay: { seconds: new anchor.BN(2) } }));
+      expect((await program.account.config.fetch(config)).configDelay.toNumber()).to.equal(2);
+
+      const mintManager = Keypair.generate().publicKey;
+      try {
+        await program.methods
+          .grantRole({ mintManager: {} }, mintManager)
+          .accounts({ configAdmin: admin.publicKey })
+          .signers([admin])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TimelockRequired');
+      }
+
+      // Payment attestors act as arbiters, so they can't be added around the delay either
+      const attestorKey = Keypair.generate().publicKey;
+      const [attestor] = PublicKey.findProgramAddressSync(
+        [Buffer.from('attestor'), attestorKey.toBuffer()],
+        program.programId
+      );
+      try {
+        await program.methods
+          .registerAttestor(attestorKey)
+          .accounts({ configAdmin: admin.publicKey, attestor })
+          .signers([admin])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TimelockRequired');
+      }
+
+      const grant = await propose({
+        grantRole: { role: { mintManager: {} }, holder: mintManager },
+      });
+      try {
+        await execute(grant);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TimelockNotElapsed');
+      }
+
+      await program.methods
+        .cancelConfigChange()
+        .accounts({ configAdmin: admin.publicKey, pendingChange: grant })
+        .signers([admin])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(grant)).to.be.null;
+
+      const reset = await propose({ setConfigDelay: { seconds: new anchor.BN(0) } });
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await execute(reset);
+
+      const state = await program.account.config.fetch(config);
+      expect(state.configDelay.toNumber()).to.equal(0);
+      expect(state.mintManager).to.be.null;
+    });
+  });
+
+  describe('emergency_withdraw', () => {
+    it('splits a paid escrow 50/50 once a pause outlasts the grace period', async () => {
+      await execute(await propose({ setEmergencyGracePeriod: { seconds: new anchor.BN(1) } }));
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const amount = AMOUNT + 1;
+
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(amount),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const withdraw = () =>
+        program.methods
+          .emergencyWithdraw()
+          .accounts({
+            caller: buyer.publicKey,
+            rentPayer: seller.publicKey,
+            depositor: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            mint,
+            escrowState,
+            vault,
+            depositorTokenAccount: sellerAta,
+            refundTokenAccount: null,
+            beneficiaryTokenAccount: buyerAta,
+            payoutTokenAccount: null,
+          })
+          .signers([buyer])
+          .rpc();
+
+      try {
+        await withdraw();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('EmergencyExitClosed');
+      }
+
+      await program.methods.pause().accounts({ pauser: admin.publicKey }).signers([admin]).rpc();
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      const sellerBefore = Number((await getAccount(provider.connection, sellerAta)).amount);
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+      await withdraw();
+
+      const sellerAfter = Number((await getAccount(provider.connection, sellerAta)).amount);
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      expect(sellerAfter - sellerBefore).to.equal(amount - Math.floor(amount / 2));
+      expect(buyerAfter - buyerBefore).to.equal(Math.floor(amount / 2));
+      expect(await provider.connection.getAccountInfo(escrowState)).to.be.null;
+
+      await program.methods
+        .unpause()
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    });
+  });
+
+  describe('denylist', () => {
+    it('blocks a denylisted buyer and routes their open escrow to dispute', async () => {
+      const [denylisted] = PublicKey.findProgramAddressSync(
+        [Buffer.from('denylist'), buyer.publicKey.toBuffer()],
+        program.programId
+      );
+      const init = (tradeId: number[]) => {
+        const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+        return program.methods
+          .initializeEscrow(
+            tradeId,
+            new anchor.BN(AMOUNT),
+            new anchor.BN(TIMEOUT),
+            SELLER_FUNDED,
+            STANDARD,
+            false,
+            null
+          )
+          .accounts({
+            depositor: seller.publicKey,
+            rentPayer: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            admin: admin.publicKey,
+            mint,
+            escrowState,
+            vault: findVaultPDA(escrowState)[0],
+            depositorTokenAccount: sellerAta,
+            rentSponsor: null,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([seller])
+          .rpc();
+      };
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      await init(tradeId);
+
+      await program.methods
+        .addToDenylist(buyer.publicKey)
+        .accounts({ configAdmin: admin.publicKey, denylisted })
+        .signers([admin])
+        .rpc();
+
+      try {
+        await init(makeTradeId());
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('WalletDenylisted');
+      }
+
+      // Nor can they lock funds in an offer of their own
+      const offerId = makeTradeId();
+      const [offer] = PublicKey.findProgramAddressSync(
+        [Buffer.from('offer'), Buffer.from(offerId), buyer.publicKey.toBuffer()],
+        program.programId
+      );
+      try {
+        await program.methods
+          .createOffer(
+            offerId,
+            new anchor.BN(AMOUNT),
+            new anchor.BN(AMOUNT),
+            new anchor.BN(AMOUNT),
+            0b1,
+            new anchor.BN(36_500_000),
+            Array.from(Buffer.from('VES')),
+            new anchor.BN(TIMEOUT)
+          )
+          .accounts({
+            seller: buyer.publicKey,
+            admin: admin.publicKey,
+            mint,
+            offer,
+            offerVault: findVaultPDA(offer)[0],
+            sellerTokenAccount: buyerAta,
+          })
+          .signers([buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('WalletDenylisted');
+      }
+
+      // Or grow the escrow they already have open
+      try {
+        await program.methods
+          .amendEscrow(new anchor.BN(2 * AMOUNT), null)
+          .accounts({
+            depositor: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            mint,
+            escrowState,
+            vault: findVaultPDA(escrowState)[0],
+            depositorTokenAccount: sellerAta,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([seller, buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('WalletDenylisted');
+      }
+
+      try {
+        await program.methods
+          .markPaid(null)
+          .accounts({
+            authority: buyer.publicKey,
+            escrowState,
+            sessionKey: null,
+            payoutTokenAccount: null,
+          })
+          .signers([buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('WalletDenylisted');
+      }
+
+      await program.methods
+        .disputeDenylistedEscrow()
+        .accounts({ configAdmin: admin.publicKey, denylisted, escrowState })
+        .signers([admin])
+        .rpc();
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.status).to.deep.equal({ disputed: {} });
+
+      await program.methods
+        .removeFromDenylist()
+        .accounts({ configAdmin: admin.publicKey, denylisted })
+        .signers([admin])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(denylisted)).to.be.null;
+    });
+  });
+
+  describe('kyc gating', () => {
+    it('requires both parties to be verified above the mint threshold', async () => {
+      const kycAttestor = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(kycAttestor.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      await program.methods
+        .grantRole({ mintManager: {} }, admin.publicKey)
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .setKycRequirement(new anchor.BN(AMOUNT), 2)
+        .accounts({ mintManager: admin.publicKey, mint })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .registerKycAttestor(kycAttestor.publicKey)
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      const kycOf = (wallet: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from('kyc'), wallet.toBuffer()],
+          program.programId
+        )[0];
+      const [attestorEntry] = PublicKey.findProgramAddressSync(
+        [Buffer.from('kyc_attestor'), kycAttestor.publicKey.toBuffer()],
+        program.programId
+      );
+      const init = (
+        amount: number,
+        withKyc: boolean,
+        attestor: PublicKey | null = withKyc ? attestorEntry : null
+      ) => {
+        const tradeId = makeTradeId();
+        const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+        return program.methods
+          .initializeEscrow(
+            tradeId,
+            new anchor.BN(amount),
+            new anchor.BN(TIMEOUT),
+            SELLER_FUNDED,
+            STANDARD,
+            false,
+            null
+          )
+          .accounts({
+            depositor: seller.publicKey,
+            rentPayer: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            admin: admin.publicKey,
+            mint,
+            escrowState,
+            vault: findVaultPDA(escrowState)[0],
+            depositorTokenAccount: sellerAta,
+            rentSponsor: null,
+            refundTokenAccount: null,
+            depositorKyc: withKyc ? kycOf(seller.publicKey) : null,
+            depositorKycAttestor: attestor,
+            beneficiaryKyc: withKyc ? kycOf(buyer.publicKey) : null,
+            beneficiaryKycAttestor: attestor,
+          })
+          .signers([seller])
+          .rpc();
+      };
+
+      // Small trades stay permissionless
+      await init(AMOUNT, false);
+
+      try {
+        await init(2 * AMOUNT, false);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('KycRequired');
+      }
+
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      const issue = (wallet: PublicKey, level: number) =>
+        program.methods
+          .issueKycAttestation(wallet, level, expiresAt)
+          .accounts({ authority: kycAttestor.publicKey })
+          .signers([kycAttestor])
+          .rpc();
+
+      // Level 1 is not enough for this mint
+      await issue(seller.publicKey, 2);
+      await issue(buyer.publicKey, 1);
+      try {
+        await init(2 * AMOUNT, true);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('KycRequired');
+      }
+
+      await issue(buyer.publicKey, 2);
+      await init(2 * AMOUNT, true);
+
+      // Removing the attestor voids its attestations, and re-registering does not revive them
+      await program.methods
+        .removeKycAttestor()
+        .accounts({ configAdmin: admin.publicKey, kycAttestor: attestorEntry })
+        .signers([admin])
+        .rpc();
+      try {
+        await init(2 * AMOUNT, true, null);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('KycRequired');
+      }
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+      await program.methods
+        .registerKycAttestor(kycAttestor.publicKey)
+        .accounts({ configAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      try {
+        await init(2 * AMOUNT, true);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('KycRequired');
+      }
+
+      await program.methods
+        .setKycRequirement(null, 0)
+        .accounts({ mintManager: admin.publicKey, mint })
+        .signers([admin])
+        .rpc();
+      for (const wallet of [seller.publicKey, buyer.publicKey]) {
+        await program.methods
+          .revokeKycAttestation()
+          .accounts({ authority: kycAttestor.publicKey, kycAttestation: kycOf(wallet) })
+          .signers([kycAttestor])
+          .rpc();
+      }
+    });
+
+    it('applies the threshold to offer fills and signed orders', async () => {
+      const setThreshold = (threshold: anchor.BN | null, level: number) =>
+        program.methods
+          .setKycRequirement(threshold, level)
+          .accounts({ mintManager: admin.publicKey, mint })
+          .signers([admin])
+          .rpc();
+      await setThreshold(new anchor.BN(AMOUNT), 2);
+
+      const offerId = makeTradeId();
+      const [offer] = PublicKey.findProgramAddressSync(
+        [Buffer.from('offer'), Buffer.from(offerId), seller.publicKey.toBuffer()],
+        program.programId
+      );
+      const [offerVault] = findVaultPDA(offer);
+      await program.methods
+        .createOffer(
+          offerId,
+          new anchor.BN(2 * AMOUNT),
+          new anchor.BN(AMOUNT),
+          new anchor.BN(2 * AMOUNT),
+          0b1,
+          new anchor.BN(36_500_000),
+          Array.from(Buffer.from('VES')),
+          new anchor.BN(TIMEOUT)
+        )
+        .accounts({
+          seller: seller.publicKey,
+          admin: admin.publicKey,
+          mint,
+          offer,
+          offerVault,
+          sellerTokenAccount: sellerAta,
+        })
+        .signers([seller])
+        .rpc();
+
+      const takeTradeId = makeTradeId();
+      const [takeEscrow] = findEscrowPDA(takeTradeId, seller.publicKey);
+      try {
+        await program.methods
+          .takeOffer(takeTradeId, new anchor.BN(2 * AMOUNT), 0)
+          .accounts({
+            buyer: buyer.publicKey,
+            mint,
+            offer,
+            offerVault,
+            escrowState: takeEscrow,
+            vault: findVaultPDA(takeEscrow)[0],
+            payoutTokenAccount: null,
+            sellerKyc: null,
+            buyerKyc: null,
+          })
+          .signers([buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('KycRequired');
+      }
+
+      const order = {
+        seller: seller.publicKey,
+        mint,
+        minAmount: new anchor.BN(AMOUNT),
+        maxAmount: new anchor.BN(2 * AMOUNT),
+        price: new anchor.BN(36_500_000),
+        fiatCurrency: Array.from(Buffer.from('VES')),
+        timeoutSeconds: new anchor.BN(TIMEOUT),
+        expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        nonce: new anchor.BN(Date.now()),
+      };
+      const message = Buffer.concat([
+        Buffer.from('mvga-escrow:order:v1'),
+        program.programId.toBuffer(),
+        program.coder.types.encode('signedOrder', order),
+      ]);
+      const [orderAuthority] = PublicKey.findProgramAddressSync(
+        [Buffer.from('order_authority')],
+        program.programId
+      );
+      await approve(provider.connection, seller, sellerAta, orderAuthority, seller, 2 * AMOUNT);
+      const [orderNonce] = PublicKey.findProgramAddressSync(
+        [Buffer.from('order_nonce'), seller.publicKey.toBuffer(), order.nonce.toBuffer('le', 8)],
+        program.programId
+      );
+      const fillTradeId = makeTradeId();
+      const [fillEscrow] = findEscrowPDA(fillTradeId, seller.publicKey);
+      try {
+        await program.methods
+          .fillSignedOrder(order, fillTradeId, new anchor.BN(2 * AMOUNT))
+          .accounts({
+            buyer: buyer.publicKey,
+            admin: admin.publicKey,
+            mint,
+            orderAuthority,
+            orderNonce,
+            escrowState: fillEscrow,
+            vault: findVaultPDA(fillEscrow)[0],
+            sellerTokenAccount: sellerAta,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            payoutTokenAccount: null,
+            sellerKyc: null,
+            buyerKyc: null,
+          })
+          .preInstructions([
+            Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: seller.secretKey,
+              message,
+            }),
+          ])
+          .signers([buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('KycRequired');
+      }
+
+      await setThreshold(null, 0);
+      await program.methods
+        .closeOffer()
+        .accounts({
+          seller: seller.publicKey,
+          mint,
+          offer,
+          offerVault,
+          sellerTokenAccount: sellerAta,
+        })
+        .signers([seller])
+        .rpc();
+    });
+
+    it('re-checks a top-up amend against the threshold', async () => {
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      // Opened below the threshold, so the escrow cannot grow past it unverified
+      await program.methods
+        .setKycRequirement(new anchor.BN(AMOUNT), 2)
+        .accounts({ mintManager: admin.publicKey, mint })
+        .signers([admin])
+        .rpc();
+      try {
+        await program.methods
+          .amendEscrow(new anchor.BN(2 * AMOUNT), null)
+          .accounts({
+            depositor: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            mint,
+            escrowState,
+            vault,
+            depositorTokenAccount: sellerAta,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([seller, buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('KycRequired');
+      }
+      await program.methods
+        .setKycRequirement(null, 0)
+        .accounts({ mintManager: admin.publicKey, mint })
+        .signers([admin])
+        .rpc();
+    });
+  });
+
+  describe('volume limits', () => {
+    it("caps a wallet's rolling 24h volume per mint", async () => {
+      // Fresh wallets so earlier tests' volume does not count
+      const trader = Keypair.generate();
+      const counterparty = Keypair.generate().publicKey;
+      const sig = await provider.connection.requestAirdrop(trader.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+      const traderAta = await createAssociatedTokenAccount(
+        provider.connection,
+        trader,
+        mint,
+        trader.publicKey
+      );
+      await mintTo(provider.connection, seller, mint, traderAta, seller, 10 * AMOUNT);
+
+      const uncapped = { daily: null, monthly: null };
+      const setLimits = (daily: number | null) =>
+        program.methods
+          .setVolumeLimits([
+            { daily: daily === null ? null : new anchor.BN(daily), monthly: null },
+            uncapped,
+            uncapped,
+            uncapped,
+          ])
+          .accounts({ mintManager: admin.publicKey, mint })
+          .signers([admin])
+          .rpc();
+      const volumeOf = (wallet: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from('volume'), wallet.toBuffer(), mint.toBuffer()],
+          program.programId
+        )[0];
+      const init = (withTrackers = true) => {
+        const tradeId = makeTradeId();
+        const [escrowState] = findEscrowPDA(tradeId, trader.publicKey);
+        return program.methods
+          .initializeEscrow(
+            tradeId,
+            new anchor.BN(2 * AMOUNT),
+            new anchor.BN(TIMEOUT),
+            SELLER_FUNDED,
+            STANDARD,
+            false,
+            null
+          )
+          .accounts({
+            depositor: trader.publicKey,
+            rentPayer: trader.publicKey,
+            beneficiary: counterparty,
+            admin: admin.publicKey,
+            mint,
+            escrowState,
+            vault: findVaultPDA(escrowState)[0],
+            depositorTokenAccount: traderAta,
+            depositorVolume: withTrackers ? volumeOf(trader.publicKey) : null,
+            beneficiaryVolume: withTrackers ? volumeOf(counterparty) : null,
+            rentSponsor: null,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([trader])
+          .rpc();
+      };
+
+      await setLimits(3 * AMOUNT);
+      // Capped wallets must bring their trackers
+      try {
+        await init(false);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('VolumeTrackerRequired');
+      }
+      await init();
+      try {
+        await init();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('DailyVolumeExceeded');
+      }
+
+      const tracker = await program.account.volumeTracker.fetch(volumeOf(trader.publicKey));
+      const recorded = tracker.hourly.reduce(
+        (sum: number, bucket: any) => sum + bucket.toNumber(),
+        0
+      );
+      expect(recorded).to.equal(2 * AMOUNT);
+
+      // Uncapped mints need no trackers at all
+      await setLimits(null);
+      await init(false);
+    });
+
+    it('counts top-up amends against the same cap', async () => {
+      // Fresh wallets so earlier tests' volume does not count
+      const trader = Keypair.generate();
+      const counterparty = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(trader.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+      const traderAta = await createAssociatedTokenAccount(
+        provider.connection,
+        trader,
+        mint,
+        trader.publicKey
+      );
+      await mintTo(provider.connection, seller, mint, traderAta, seller, 10 * AMOUNT);
+
+      const uncapped = { daily: null, monthly: null };
+      const setLimits = (daily: anchor.BN | null) =>
+        program.methods
+          .setVolumeLimits([{ daily, monthly: null }, uncapped, uncapped, uncapped])
+          .accounts({ mintManager: admin.publicKey, mint })
+          .signers([admin])
+          .rpc();
+      await setLimits(new anchor.BN(3 * AMOUNT));
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, trader.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(2 * AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: trader.publicKey,
+          rentPayer: trader.publicKey,
+          beneficiary: counterparty.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: traderAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([trader])
+        .rpc();
+
+      // 2 opened + 2 topped up is over the 3 allowed in 24h
+      try {
+        await program.methods
+          .amendEscrow(new anchor.BN(4 * AMOUNT), null)
+          .accounts({
+            depositor: trader.publicKey,
+            beneficiary: counterparty.publicKey,
+            mint,
+            escrowState,
+            vault,
+            depositorTokenAccount: traderAta,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([trader, counterparty])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('DailyVolumeExceeded');
+      }
+
+      await setLimits(null);
+    });
+  });
+
+  describe('global stats and tvl cap', () => {
+    it('tracks locked value per mint and enforces the tvl cap', async () => {
+      const [globalStats] = PublicKey.findProgramAddressSync(
+        [Buffer.from('global_stats'), mint.toBuffer()],
+        program.programId
+      );
+      const init = (tradeId = makeTradeId()) => {
+        const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+        return program.methods
+          .initializeEscrow(
+            tradeId,
+            new anchor.BN(AMOUNT),
+            new anchor.BN(TIMEOUT),
+            SELLER_FUNDED,
+            STANDARD,
+            false,
+            null
+          )
+          .accounts({
+            depositor: seller.publicKey,
+            rentPayer: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            admin: admin.publicKey,
+            mint,
+            escrowState,
+            vault: findVaultPDA(escrowState)[0],
+            depositorTokenAccount: sellerAta,
+            rentSponsor: null,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([seller])
+          .rpc();
+      };
+      const setCap = (cap: anchor.BN | null) =>
+        program.methods
+          .setTvlCap(cap)
+          .accounts({ mintManager: admin.publicKey, mint })
+          .signers([admin])
+          .rpc();
+
+      const before = await program.account.globalStats.fetch(globalStats);
+      const tradeId = makeTradeId();
+      await init(tradeId);
+      const after = await program.account.globalStats.fetch(globalStats);
+      expect(after.totalLocked.sub(before.totalLocked).toNumber()).to.equal(AMOUNT);
+      expect(after.locked.sub(before.locked).toNumber()).to.equal(1);
+      expect(after.totalEscrows.sub(before.totalEscrows).toNumber()).to.equal(1);
+
+      await setCap(after.totalLocked.addn(AMOUNT / 2));
+      try {
+        await init();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TvlCapExceeded');
+      }
+
+      // Topping up an open escrow is held to the same cap
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      try {
+        await program.methods
+          .amendEscrow(new anchor.BN(2 * AMOUNT), null)
+          .accounts({
+            depositor: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            mint,
+            escrowState,
+            vault: findVaultPDA(escrowState)[0],
+            depositorTokenAccount: sellerAta,
+            refundTokenAccount: null,
+            depositorKyc: null,
+            beneficiaryKyc: null,
+          })
+          .signers([seller, buyer])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TvlCapExceeded');
+      }
+
+      // Offer liquidity is counted and capped when it is deposited
+      const offerId = makeTradeId();
+      const [offer] = PublicKey.findProgramAddressSync(
+        [Buffer.from('offer'), Buffer.from(offerId), seller.publicKey.toBuffer()],
+        program.programId
+      );
+      const [offerVault] = findVaultPDA(offer);
+      const createOffer = () =>
+        program.methods
+          .createOffer(
+            offerId,
+            new anchor.BN(AMOUNT),
+            new anchor.BN(AMOUNT),
+            new anchor.BN(AMOUNT),
+            0b1,
+            new anchor.BN(36_500_000),
+            Array.from(Buffer.from('VES')),
+            new anchor.BN(TIMEOUT)
+          )
+          .accounts({
+            seller: seller.publicKey,
+            admin: admin.publicKey,
+            mint,
+            offer,
+            offerVault,
+            sellerTokenAccount: sellerAta,
+          })
+          .signers([seller])
+          .rpc();
+      try {
+        await createOffer();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('TvlCapExceeded');
+      }
+
+      await setCap(null);
+      await createOffer();
+      const funded = await program.account.globalStats.fetch(globalStats);
+      expect(funded.totalLocked.sub(after.totalLocked).toNumber()).to.equal(AMOUNT);
+
+      // Filling it moves tokens that are already counted, so a cap at the current total holds
+      await setCap(funded.totalLocked);
+      const fillTradeId = makeTradeId();
+      const [fillEscrow] = findEscrowPDA(fillTradeId, seller.publicKey);
+      await program.methods
+        .takeOffer(fillTradeId, new anchor.BN(AMOUNT), 0)
+        .accounts({
+          buyer: buyer.publicKey,
+          mint,
+          offer,
+          offerVault,
+          escrowState: fillEscrow,
+          vault: findVaultPDA(fillEscrow)[0],
+          payoutTokenAccount: null,
+          sellerKyc: null,
+          buyerKyc: null,
+        })
+        .signers([buyer])
+        .rpc();
+      const filled = await program.account.globalStats.fetch(globalStats);
+      expect(filled.totalLocked.eq(funded.totalLocked)).to.be.true;
+      expect(filled.locked.sub(funded.locked).toNumber()).to.equal(1);
+
+      await setCap(null);
+      await init();
+    });
+  });
+
+  describe('referrals', () => {
+    it("shares the platform fee with the buyer's referrer on release", async () => {
+      const referrer = Keypair.generate();
+      const treasury = Keypair.generate().publicKey;
+      const sig = await provider.connection.requestAirdrop(referrer.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+      const referrerAta = await createAssociatedTokenAccount(
+        provider.connection,
+        referrer,
+        mint,
+        referrer.publicKey
+      );
+      const treasuryAta = await createAssociatedTokenAccount(
+        provider.connection,
+        seller,
+        mint,
+        treasury
+      );
+      const [referrerStats] = PublicKey.findProgramAddressSync(
+        [Buffer.from('referrer'), referrer.publicKey.toBuffer(), mint.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .registerReferrer()
+        .accounts({ referrer: referrer.publicKey, mint })
+        .signers([referrer])
+        .rpc();
+      // 1% fee, a fifth of it to each referrer
+      await program.methods
+        .setFees(100, 2_000, treasury)
+        .accounts({ feeManager: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          STANDARD,
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+          depositorReferrer: null,
+          beneficiaryReferrer: referrerStats,
+        })
+        .signers([seller])
+        .rpc();
+      const escrow = await program.account.escrowState.fetch(escrowState);
+      expect(escrow.beneficiaryReferrer.toBase58()).to.equal(referrer.publicKey.toBase58());
+      expect(escrow.depositorReferrer).to.be.null;
+
+      await program.methods
+        .markPaid(null)
+        .accounts({
+          authority: buyer.publicKey,
+          escrowState,
+          sessionKey: null,
+          payoutTokenAccount: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const release = (beneficiaryReferrer: PublicKey | null) =>
+        program.methods
+          .releaseEscrow()
+          .accounts({
+            authority: seller.publicKey,
+            payer: seller.publicKey,
+            rentPayer: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            mint,
+            escrowState,
+            vault,
+            beneficiaryTokenAccount: buyerAta,
+            payoutTokenAccount: null,
+            sessionKey: null,
+            feeTokenAccount: treasuryAta,
+            depositorReferrer: null,
+            depositorReferrerTokenAccount: null,
+            beneficiaryReferrer,
+            beneficiaryReferrerTokenAccount: referrerAta,
+          })
+          .signers([seller])
+          .rpc();
+
+      try {
+        await release(null);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('InvalidReferrer');
+      }
+
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+      await release(referrerStats);
+
+      const fee = AMOUNT / 100;
+      const share = fee / 5;
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      expect(buyerAfter - buyerBefore).to.equal(AMOUNT - fee);
+      expect(Number((await getAccount(provider.connection, referrerAta)).amount)).to.equal(share);
+      expect(Number((await getAccount(provider.connection, treasuryAta)).amount)).to.equal(
+        fee - share
+      );
+
+      const stats = await program.account.referrerStats.fetch(referrerStats);
+      expect(stats.referrals.toNumber()).to.equal(1);
+      expect(stats.referredVolume.toNumber()).to.equal(AMOUNT);
+      expect(stats.earnings.toNumber()).to.equal(share);
+
+      await program.methods
+        .setFees(0, 0, treasury)
+        .accounts({ feeManager: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    });
+
+    it('takes the platform fee on hashlock claims too', async () => {
+      const treasury = Keypair.generate().publicKey;
+      const treasuryAta = await createAssociatedTokenAccount(
+        provider.connection,
+        seller,
+        mint,
+        treasury
+      );
+      await program.methods
+        .setFees(100, 0, treasury)
+        .accounts({ feeManager: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const preimage = randomBytes(32);
+      const hash = Array.from(createHash('sha256').update(preimage).digest());
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          { hashlock: { algorithm: { sha256: {} }, hash } },
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const claim = (feeTokenAccount: PublicKey | null) =>
+        program.methods
+          .claimWithPreimage(preimage)
+          .accounts({
+            submitter: admin.publicKey,
+            beneficiary: buyer.publicKey,
+            rentPayer: seller.publicKey,
+            mint,
+            escrowState,
+            vault,
+            beneficiaryTokenAccount: buyerAta,
+            payoutTokenAccount: null,
+            feeTokenAccount,
+            depositorReferrer: null,
+            depositorReferrerTokenAccount: null,
+            beneficiaryReferrer: null,
+            beneficiaryReferrerTokenAccount: null,
+          })
+          .signers([admin])
+          .rpc();
+
+      try {
+        await claim(null);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('InvalidFeeAccount');
+      }
+
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+      await claim(treasuryAta);
+      const fee = AMOUNT / 100;
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      expect(buyerAfter - buyerBefore).to.equal(AMOUNT - fee);
+      expect(Number((await getAccount(provider.connection, treasuryAta)).amount)).to.equal(fee);
+
+      await program.methods
+        .setFees(0, 0, treasury)
+        .accounts({ feeManager: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    });
+
+    it('takes the platform fee on the vested payout when vesting is cancelled', async () => {
+      const treasury = Keypair.generate().publicKey;
+      const treasuryAta = await createAssociatedTokenAccount(
+        provider.connection,
+        seller,
+        mint,
+        treasury
+      );
+      await program.methods
+        .setFees(100, 0, treasury)
+        .accounts({ feeManager: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      const tradeId = makeTradeId();
+      const [escrowState] = findEscrowPDA(tradeId, seller.publicKey);
+      const [vault] = findVaultPDA(escrowState);
+      const now = Math.floor(Date.now() / 1000);
+      await program.methods
+        .initializeEscrow(
+          tradeId,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(TIMEOUT),
+          SELLER_FUNDED,
+          { vesting: { start: new anchor.BN(now - 3600), end: new anchor.BN(now + 3600) } },
+          false,
+          null
+        )
+        .accounts({
+          depositor: seller.publicKey,
+          rentPayer: seller.publicKey,
+          beneficiary: buyer.publicKey,
+          admin: admin.publicKey,
+          mint,
+          escrowState,
+          vault,
+          depositorTokenAccount: sellerAta,
+          rentSponsor: null,
+          refundTokenAccount: null,
+          depositorKyc: null,
+          beneficiaryKyc: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      // The beneficiary never claims — the vested half is paid out by the cancel instead
+      const cancel = (feeTokenAccount: PublicKey | null) =>
+        program.methods
+          .cancelVesting()
+          .accounts({
+            depositor: seller.publicKey,
+            rentPayer: seller.publicKey,
+            beneficiary: buyer.publicKey,
+            mint,
+            escrowState,
+            vault,
+            beneficiaryTokenAccount: buyerAta,
+            payoutTokenAccount: null,
+            depositorTokenAccount: sellerAta,
+            refundTokenAccount: null,
+            feeTokenAccount,
+            depositorReferrer: null,
+            depositorReferrerTokenAccount: null,
+            beneficiaryReferrer: null,
+            beneficiaryReferrerTokenAccount: null,
+          })
+          .signers([seller])
+          .rpc();
+
+      try {
+        await cancel(null);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal('InvalidFeeAccount');
+      }
+
+      const buyerBefore = Number((await getAccount(provider.connection, buyerAta)).amount);
+      const sellerBefore = Number((await getAccount(provider.connection, sellerAta)).amount);
+      await cancel(treasuryAta);
+      const buyerAfter = Number((await getAccount(provider.connection, buyerAta)).amount);
+      const sellerAfter = Number((await getAccount(provider.connection, sellerAta)).amount);
+      const fee = Number((await getAccount(provider.connection, treasuryAta)).amount);
+      const vestedPaid = buyerAfter - buyerBefore + fee;
+      expect(fee).to.equal(Math.floor(vestedPaid / 100));
+      expect(fee).to.be.greaterThan(0);
+      expect(vestedPaid + (sellerAfter - sellerBefore)).to.equal(AMOUNT);
+
+      await program.methods
+        .setFees(0, 0, treasury)
+        .accounts({ feeManager: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    });
+  });
 });
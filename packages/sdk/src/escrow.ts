@@ -30,8 +30,8 @@ export type EscrowStatus = 'locked' | 'paymentSent' | 'released' | 'refunded' |
 
 export interface EscrowAccount {
   tradeId: number[];
-  seller: PublicKey;
-  buyer: PublicKey;
+  depositor: PublicKey;
+  beneficiary: PublicKey;
   mint: PublicKey;
   amount: BN;
   status: EscrowStatus;
@@ -39,11 +39,65 @@ export interface EscrowAccount {
   lockedAt: BN;
   timeoutSeconds: BN;
   bump: number;
+  direction: EscrowDirection;
+  mode: EscrowMode;
+  milestones: EscrowMilestone[];
+  nextMilestone: number;
+  attestedRelease: boolean;
+  rentPayer: PublicKey;
+  payoutAccount: PublicKey | null;
+  refundAccount: PublicKey | null;
+  metadata: TradeMetadata | null;
+  paymentReference: { referenceHash: number[]; fiatAmount: BN } | null;
+  paymentReferenceUpdated: boolean;
+  /** Layout version — escrows opened before versioning need `migrate_escrow` */
+  version: number;
+}
+
+export interface TradeMetadata {
+  /** ISO 4217 code, e.g. 'VES' */
+  fiatCurrency: string;
+  /** Fiat amount owed, in minor units */
+  fiatAmount: BN;
+  /** Fiat price per whole token, in minor units */
+  price: BN;
+  paymentMethod: PaymentMethod;
+  /** 32-byte hash of the off-chain trade terms */
+  termsHash: Uint8Array;
+}
+
+/** Mirrors the backend's PaymentMethod enum order */
+export enum PaymentMethod {
+  Zelle = 0,
+  Venmo = 1,
+  Paypal = 2,
+  BankTransfer = 3,
+  PagoMovil = 4,
+  BinancePay = 5,
 }
 
+export type EscrowMode = 'standard' | 'milestones' | 'vesting' | 'hashlock';
+
+export interface EscrowMilestone {
+  amount: BN;
+  deadline: BN | null;
+}
+
+export enum EscrowDirection {
+  SellerFunded = 0,
+  BuyerFunded = 1,
+}
+
+/** Session key permission bits — combine with `|` when creating a session */
+export const SessionPermission = {
+  MarkPaid: 1 << 0,
+  Release: 1 << 1,
+  FileDispute: 1 << 2,
+} as const;
+
 export enum Resolution {
-  ReleaseToBuyer = 0,
-  RefundToSeller = 1,
+  ReleaseToBeneficiary = 0,
+  RefundToDepositor = 1,
 }
 
 // ---------------------------------------------------------------------------
@@ -71,6 +125,131 @@ export function findVaultPDA(
   );
 }
 
+export function findSessionKeyPDA(
+  owner: PublicKey,
+  delegate: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('session'), owner.toBytes(), delegate.toBytes()],
+    programId
+  );
+}
+
+export function findRentSponsorPDA(
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([new TextEncoder().encode('rent_sponsor')], programId);
+}
+
+/**
+ * Derive the program config PDA holding role assignments and the pause flag.
+ * Seeds: ["config"]
+ */
+export function findConfigPDA(programId: PublicKey = ESCROW_PROGRAM_ID): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([new TextEncoder().encode('config')], programId);
+}
+
+/**
+ * Derive a mint's policy PDA (KYC threshold and other per-mint limits).
+ * Seeds: ["mint_policy", mint]
+ */
+export function findMintPolicyPDA(
+  mint: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('mint_policy'), mint.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a KYC attestor's registry PDA. Its attestations only count while it exists.
+ * Seeds: ["kyc_attestor", authority]
+ */
+export function findKycAttestorPDA(
+  authority: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('kyc_attestor'), authority.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a wallet's KYC attestation PDA.
+ * Seeds: ["kyc", wallet]
+ */
+export function findKycAttestationPDA(
+  wallet: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('kyc'), wallet.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a wallet's rolling volume tracker for one mint.
+ * Seeds: ["volume", wallet, mint]
+ */
+export function findVolumeTrackerPDA(
+  wallet: PublicKey,
+  mint: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('volume'), wallet.toBuffer(), mint.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a mint's escrow statistics PDA.
+ * Seeds: ["global_stats", mint]
+ */
+export function findGlobalStatsPDA(
+  mint: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('global_stats'), mint.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a referrer's stats PDA for one mint, created by `register_referrer`.
+ * Seeds: ["referrer", referrer, mint]
+ */
+export function findReferrerStatsPDA(
+  referrer: PublicKey,
+  mint: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('referrer'), referrer.toBuffer(), mint.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a wallet's denylist PDA. The wallet is denylisted when the account exists.
+ * Seeds: ["denylist", wallet]
+ */
+export function findDenylistPDA(
+  wallet: PublicKey,
+  programId: PublicKey = ESCROW_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode('denylist'), wallet.toBuffer()],
+    programId
+  );
+}
+
 // ---------------------------------------------------------------------------
 // Trade ID helper — convert UUID string to 16-byte array
 // ---------------------------------------------------------------------------
@@ -111,6 +290,8 @@ const DISCRIMINATORS = {
   refundEscrow: new Uint8Array([107, 186, 89, 99, 26, 194, 23, 204]),
   fileDispute: new Uint8Array([210, 63, 221, 114, 212, 97, 195, 156]),
   resolveDispute: new Uint8Array([231, 6, 202, 6, 96, 103, 12, 230]),
+  extendTimeout: new Uint8Array([246, 84, 96, 134, 76, 55, 57, 33]),
+  emergencyWithdraw: new Uint8Array([239, 45, 203, 64, 150, 73, 218, 92]),
 };
 
 // ---------------------------------------------------------------------------
@@ -122,13 +303,77 @@ function writeU64LE(buf: Uint8Array, value: bigint, offset: number): void {
   view.setBigUint64(offset, value, true);
 }
 
+// Optional accounts Anchor treats as absent are passed as the program ID
+function optionalMeta(pubkey: PublicKey | undefined, programId: PublicKey, isWritable: boolean) {
+  if (!pubkey) return { pubkey: programId, isSigner: false, isWritable: false };
+  return { pubkey, isSigner: false, isWritable };
+}
+
+function sessionKeyMeta(
+  owner: PublicKey,
+  delegate: PublicKey | undefined,
+  programId: PublicKey,
+  isWritable: boolean
+) {
+  const sessionKey = delegate && findSessionKeyPDA(owner, delegate, programId)[0];
+  return optionalMeta(sessionKey, programId, isWritable);
+}
+
+// A party's KYC attestation and its attestor's registry entry, or two absent accounts
+function kycMetas(wallet: PublicKey, attestor: PublicKey | undefined, programId: PublicKey) {
+  return [
+    optionalMeta(attestor && findKycAttestationPDA(wallet, programId)[0], programId, false),
+    optionalMeta(attestor && findKycAttestorPDA(attestor, programId)[0], programId, false),
+  ];
+}
+
+// Stats PDA of a referrer, when the party has one
+function referrerStats(
+  referrer: PublicKey | undefined,
+  mint: PublicKey,
+  programId: PublicKey
+): PublicKey | undefined {
+  return referrer ? findReferrerStatsPDA(referrer, mint, programId)[0] : undefined;
+}
+
+// Fee recipient's ATA, then each referrer's stats and ATA — absent accounts when not given
+function feeMetas(
+  params: {
+    mint: PublicKey;
+    feeRecipient?: PublicKey;
+    sellerReferrer?: PublicKey;
+    buyerReferrer?: PublicKey;
+  },
+  programId: PublicKey
+) {
+  const ata = (owner?: PublicKey) =>
+    owner ? getAssociatedTokenAddressSync(params.mint, owner) : undefined;
+  return [
+    optionalMeta(ata(params.feeRecipient), programId, true),
+    optionalMeta(referrerStats(params.sellerReferrer, params.mint, programId), programId, true),
+    optionalMeta(ata(params.sellerReferrer), programId, true),
+    optionalMeta(referrerStats(params.buyerReferrer, params.mint, programId), programId, true),
+    optionalMeta(ata(params.buyerReferrer), programId, true),
+  ];
+}
+
 // ---------------------------------------------------------------------------
 // Instruction builders
 // ---------------------------------------------------------------------------
 
 /**
  * Build an `initialize_escrow` instruction.
- * Seller locks `amount` tokens into a PDA vault.
+ * `seller` is the depositor and locks `amount` tokens into a PDA vault; `buyer` is the
+ * beneficiary. Pass `direction: BuyerFunded` when the paying side is the depositor, and
+ * `attestedRelease: true` to let a registered payment attestor release once marked paid.
+ * `rentPayer` (default: seller) pays the escrow rent and gets it back on close; set
+ * `sponsored` when `rentPayer` is the rent sponsor's operator so the pool reimburses it.
+ * `refundAccount` sends refunds to a token account other than the seller's own, and
+ * `metadata` records the fiat side of the trade on-chain. Pass `sellerKycAttestor` and
+ * `buyerKycAttestor` (the attestors that verified each party) to include their KYC
+ * attestations, needed when `amount` is above the mint's KYC threshold. Set `trackVolume`
+ * on mints that cap wallet volume to pass both parties' volume trackers, created on first use.
+ * `sellerReferrer` and `buyerReferrer` attribute either party to a registered referrer.
  */
 export function buildInitializeEscrowIx(params: {
   seller: PublicKey;
@@ -138,6 +383,17 @@ export function buildInitializeEscrowIx(params: {
   tradeId: number[];
   amount: BN;
   timeoutSeconds: BN;
+  direction?: EscrowDirection;
+  attestedRelease?: boolean;
+  rentPayer?: PublicKey;
+  sponsored?: boolean;
+  refundAccount?: PublicKey;
+  metadata?: TradeMetadata;
+  sellerKycAttestor?: PublicKey;
+  buyerKycAttestor?: PublicKey;
+  trackVolume?: boolean;
+  sellerReferrer?: PublicKey;
+  buyerReferrer?: PublicKey;
   programId?: PublicKey;
 }): TransactionInstruction {
   // Client-side validation to prevent wasted transaction fees
@@ -153,31 +409,69 @@ export function buildInitializeEscrowIx(params: {
   if (params.seller.equals(params.buyer)) {
     throw new Error('Buyer and seller cannot be the same account');
   }
+  if (params.metadata && params.metadata.fiatCurrency.length !== 3) {
+    throw new Error('Fiat currency must be a 3-letter ISO 4217 code');
+  }
+  if (params.metadata && params.metadata.termsHash.length !== 32) {
+    throw new Error('Terms hash must be 32 bytes');
+  }
 
   const programId = params.programId ?? ESCROW_PROGRAM_ID;
   const [escrowState] = findEscrowPDA(params.tradeId, params.seller, programId);
   const [vault] = findVaultPDA(escrowState, programId);
   const sellerAta = getAssociatedTokenAddressSync(params.mint, params.seller);
+  const rentSponsor = params.sponsored ? findRentSponsorPDA(programId)[0] : undefined;
+  const [config] = findConfigPDA(programId);
+  const volume = (wallet: PublicKey) =>
+    params.trackVolume ? findVolumeTrackerPDA(wallet, params.mint, programId)[0] : undefined;
 
   // Serialize instruction data: discriminator + trade_id[16] + amount(u64) + timeout(u64)
-  const data = new Uint8Array(8 + 16 + 8 + 8);
+  // + direction enum (1 byte) + terms enum (1 byte, Standard — milestone terms not supported here)
+  // + attested_release (bool) + metadata option (1 byte tag, then currency[3] + fiat_amount(u64)
+  // + price(u64) + payment_method enum (1 byte) + terms_hash[32] when present)
+  const data = new Uint8Array(8 + 16 + 8 + 8 + 1 + 1 + 1 + 1 + (params.metadata ? 52 : 0));
   data.set(DISCRIMINATORS.initializeEscrow, 0);
   data.set(new Uint8Array(params.tradeId), 8);
   writeU64LE(data, BigInt(params.amount.toString()), 24);
   writeU64LE(data, BigInt(params.timeoutSeconds.toString()), 32);
+  data[40] = params.direction ?? EscrowDirection.SellerFunded;
+  data[41] = 0;
+  data[42] = params.attestedRelease ? 1 : 0;
+  if (params.metadata) {
+    data[43] = 1;
+    data.set(new TextEncoder().encode(params.metadata.fiatCurrency), 44);
+    writeU64LE(data, BigInt(params.metadata.fiatAmount.toString()), 47);
+    writeU64LE(data, BigInt(params.metadata.price.toString()), 55);
+    data[63] = params.metadata.paymentMethod;
+    data.set(params.metadata.termsHash, 64);
+  }
 
   return new TransactionInstruction({
     programId,
     keys: [
       { pubkey: params.seller, isSigner: true, isWritable: true },
+      { pubkey: params.rentPayer ?? params.seller, isSigner: true, isWritable: true },
       { pubkey: params.buyer, isSigner: false, isWritable: false },
       { pubkey: params.admin, isSigner: false, isWritable: false },
       { pubkey: params.mint, isSigner: false, isWritable: false },
+      { pubkey: config, isSigner: false, isWritable: false },
       { pubkey: escrowState, isSigner: false, isWritable: true },
       { pubkey: vault, isSigner: false, isWritable: true },
       { pubkey: sellerAta, isSigner: false, isWritable: true },
+      { pubkey: findDenylistPDA(params.seller, programId)[0], isSigner: false, isWritable: false },
+      { pubkey: findDenylistPDA(params.buyer, programId)[0], isSigner: false, isWritable: false },
+      { pubkey: findMintPolicyPDA(params.mint, programId)[0], isSigner: false, isWritable: false },
+      optionalMeta(volume(params.seller), programId, true),
+      optionalMeta(volume(params.buyer), programId, true),
+      { pubkey: findGlobalStatsPDA(params.mint, programId)[0], isSigner: false, isWritable: true },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      optionalMeta(rentSponsor, programId, true),
+      optionalMeta(params.refundAccount, programId, false),
+      ...kycMetas(params.seller, params.sellerKycAttestor, programId),
+      ...kycMetas(params.buyer, params.buyerKycAttestor, programId),
+      optionalMeta(referrerStats(params.sellerReferrer, params.mint, programId), programId, true),
+      optionalMeta(referrerStats(params.buyerReferrer, params.mint, programId), programId, true),
     ],
     data: Buffer.from(data),
   });
@@ -185,52 +479,101 @@ export function buildInitializeEscrowIx(params: {
 
 /**
  * Build a `mark_paid` instruction.
- * Buyer signals off-chain payment was sent.
+ * Beneficiary signals the off-chain leg (payment or delivery) is done. Pass `delegate`
+ * to sign with the buyer's session key instead of the buyer's wallet, and `payoutAccount`
+ * to be paid into a token account other than the buyer's ATA (buyer's wallet only, not a
+ * delegate). `paymentReference` tells the seller which transfer to look for: a hash of the
+ * bank reference and the fiat amount sent.
  */
 export function buildMarkPaidIx(params: {
   buyer: PublicKey;
   escrowState: PublicKey;
+  mint: PublicKey;
+  delegate?: PublicKey;
+  payoutAccount?: PublicKey;
+  paymentReference?: { referenceHash: Uint8Array; fiatAmount: BN };
   programId?: PublicKey;
 }): TransactionInstruction {
   const programId = params.programId ?? ESCROW_PROGRAM_ID;
+  const reference = params.paymentReference;
+  if (reference && reference.referenceHash.length !== 32) {
+    throw new Error('Reference hash must be 32 bytes');
+  }
+  if (params.delegate && params.payoutAccount) {
+    throw new Error('Session keys cannot change the payout account');
+  }
+
+  // Serialize: discriminator + payment_reference option (1 byte tag, then
+  // reference_hash[32] + fiat_amount(u64) when present)
+  const data = new Uint8Array(8 + 1 + (reference ? 40 : 0));
+  data.set(DISCRIMINATORS.markPaid, 0);
+  if (reference) {
+    data[8] = 1;
+    data.set(reference.referenceHash, 9);
+    writeU64LE(data, BigInt(reference.fiatAmount.toString()), 41);
+  }
 
   return new TransactionInstruction({
     programId,
     keys: [
-      { pubkey: params.buyer, isSigner: true, isWritable: false },
+      { pubkey: params.delegate ?? params.buyer, isSigner: true, isWritable: false },
       { pubkey: params.escrowState, isSigner: false, isWritable: true },
+      { pubkey: findGlobalStatsPDA(params.mint, programId)[0], isSigner: false, isWritable: true },
+      { pubkey: findDenylistPDA(params.buyer, programId)[0], isSigner: false, isWritable: false },
+      sessionKeyMeta(params.buyer, params.delegate, programId, false),
+      optionalMeta(params.payoutAccount, programId, false),
     ],
-    data: Buffer.from(DISCRIMINATORS.markPaid),
+    data: Buffer.from(data),
   });
 }
 
 /**
  * Build a `release_escrow` instruction.
- * Seller confirms off-chain payment received; tokens go to buyer.
+ * Seller confirms off-chain payment received; tokens go to buyer. Pass `delegate`
+ * to sign with the seller's session key instead of the seller's wallet, `payer` to have
+ * a relayer cover fees, and `rentPayer` when someone other than the seller paid the rent.
+ * Pass `payoutAccount` when the buyer chose one at `mark_paid`. When a platform fee is set,
+ * pass `feeRecipient` (the config's fee wallet) and the escrow's referrers, whose ATAs are
+ * paid their share of the fee.
  */
 export function buildReleaseEscrowIx(params: {
   seller: PublicKey;
   buyer: PublicKey;
   mint: PublicKey;
   escrowState: PublicKey;
+  delegate?: PublicKey;
+  payer?: PublicKey;
+  rentPayer?: PublicKey;
+  payoutAccount?: PublicKey;
+  feeRecipient?: PublicKey;
+  sellerReferrer?: PublicKey;
+  buyerReferrer?: PublicKey;
   programId?: PublicKey;
 }): TransactionInstruction {
   const programId = params.programId ?? ESCROW_PROGRAM_ID;
+  const authority = params.delegate ?? params.seller;
   const [vault] = findVaultPDA(params.escrowState, programId);
   const buyerAta = getAssociatedTokenAddressSync(params.mint, params.buyer);
 
   return new TransactionInstruction({
     programId,
     keys: [
-      { pubkey: params.seller, isSigner: true, isWritable: true },
+      { pubkey: authority, isSigner: true, isWritable: false },
+      { pubkey: params.payer ?? authority, isSigner: true, isWritable: true },
+      { pubkey: params.rentPayer ?? params.seller, isSigner: false, isWritable: true },
       { pubkey: params.buyer, isSigner: false, isWritable: true },
       { pubkey: params.mint, isSigner: false, isWritable: false },
+      { pubkey: findConfigPDA(programId)[0], isSigner: false, isWritable: false },
       { pubkey: params.escrowState, isSigner: false, isWritable: true },
+      { pubkey: findGlobalStatsPDA(params.mint, programId)[0], isSigner: false, isWritable: true },
       { pubkey: vault, isSigner: false, isWritable: true },
-      { pubkey: buyerAta, isSigner: false, isWritable: true },
+      optionalMeta(params.payoutAccount ? undefined : buyerAta, programId, true),
+      optionalMeta(params.payoutAccount, programId, true),
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      sessionKeyMeta(params.seller, params.delegate, programId, true),
+      ...feeMetas(params, programId),
     ],
     data: Buffer.from(DISCRIMINATORS.releaseEscrow),
   });
@@ -238,10 +581,12 @@ export function buildReleaseEscrowIx(params: {
 
 /**
  * Build a `refund_escrow` instruction.
- * Seller self-refunds after timeout has elapsed.
+ * Seller self-refunds after timeout has elapsed, into `refundAccount` if one was chosen.
  */
 export function buildRefundEscrowIx(params: {
   seller: PublicKey;
+  rentPayer?: PublicKey;
+  refundAccount?: PublicKey;
   mint: PublicKey;
   escrowState: PublicKey;
   programId?: PublicKey;
@@ -254,10 +599,13 @@ export function buildRefundEscrowIx(params: {
     programId,
     keys: [
       { pubkey: params.seller, isSigner: true, isWritable: true },
+      { pubkey: params.rentPayer ?? params.seller, isSigner: false, isWritable: true },
       { pubkey: params.mint, isSigner: false, isWritable: false },
       { pubkey: params.escrowState, isSigner: false, isWritable: true },
+      { pubkey: findGlobalStatsPDA(params.mint, programId)[0], isSigner: false, isWritable: true },
       { pubkey: vault, isSigner: false, isWritable: true },
-      { pubkey: sellerAta, isSigner: false, isWritable: true },
+      optionalMeta(params.refundAccount ? undefined : sellerAta, programId, true),
+      optionalMeta(params.refundAccount, programId, true),
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ],
@@ -265,13 +613,95 @@ export function buildRefundEscrowIx(params: {
   });
 }
 
+/**
+ * Build an `extend_timeout` instruction.
+ * Both seller and buyer sign to push back the seller's self-refund. The timeout still
+ * counts from when the escrow was locked and cannot exceed 30 days.
+ */
+export function buildExtendTimeoutIx(params: {
+  seller: PublicKey;
+  buyer: PublicKey;
+  escrowState: PublicKey;
+  newTimeoutSeconds: BN;
+  programId?: PublicKey;
+}): TransactionInstruction {
+  if (params.newTimeoutSeconds.gt(new BN(30 * 24 * 3600))) {
+    throw new Error('Timeout exceeds maximum (30 days)');
+  }
+  const programId = params.programId ?? ESCROW_PROGRAM_ID;
+
+  // Serialize: discriminator + new_timeout_seconds (u64)
+  const data = new Uint8Array(8 + 8);
+  data.set(DISCRIMINATORS.extendTimeout, 0);
+  writeU64LE(data, BigInt(params.newTimeoutSeconds.toString()), 8);
+
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: params.seller, isSigner: true, isWritable: false },
+      { pubkey: params.buyer, isSigner: true, isWritable: false },
+      { pubkey: params.escrowState, isSigner: false, isWritable: true },
+    ],
+    data: Buffer.from(data),
+  });
+}
+
+/**
+ * Build an `emergency_withdraw` instruction.
+ * Callable by anyone once the program has been paused longer than the emergency grace
+ * period. Locked funds go back to the seller; once the buyer marked paid (or a dispute was
+ * filed) they are split 50/50. `caller` pays for any missing associated token accounts.
+ */
+export function buildEmergencyWithdrawIx(params: {
+  caller: PublicKey;
+  seller: PublicKey;
+  buyer: PublicKey;
+  rentPayer?: PublicKey;
+  mint: PublicKey;
+  escrowState: PublicKey;
+  refundAccount?: PublicKey;
+  payoutAccount?: PublicKey;
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const programId = params.programId ?? ESCROW_PROGRAM_ID;
+  const [config] = findConfigPDA(programId);
+  const [vault] = findVaultPDA(params.escrowState, programId);
+  const sellerAta = getAssociatedTokenAddressSync(params.mint, params.seller);
+  const buyerAta = getAssociatedTokenAddressSync(params.mint, params.buyer);
+
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: params.caller, isSigner: true, isWritable: true },
+      { pubkey: config, isSigner: false, isWritable: false },
+      { pubkey: params.rentPayer ?? params.seller, isSigner: false, isWritable: true },
+      { pubkey: params.seller, isSigner: false, isWritable: false },
+      { pubkey: params.buyer, isSigner: false, isWritable: false },
+      { pubkey: params.mint, isSigner: false, isWritable: false },
+      { pubkey: params.escrowState, isSigner: false, isWritable: true },
+      { pubkey: findGlobalStatsPDA(params.mint, programId)[0], isSigner: false, isWritable: true },
+      { pubkey: vault, isSigner: false, isWritable: true },
+      optionalMeta(params.refundAccount ? undefined : sellerAta, programId, true),
+      optionalMeta(params.refundAccount, programId, true),
+      optionalMeta(params.payoutAccount ? undefined : buyerAta, programId, true),
+      optionalMeta(params.payoutAccount, programId, true),
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.from(DISCRIMINATORS.emergencyWithdraw),
+  });
+}
+
 /**
  * Build a `file_dispute` instruction.
- * Either buyer or seller can file.
+ * Either buyer or seller can file. Pass `delegate` to sign with the disputer's session key.
  */
 export function buildFileDisputeIx(params: {
   disputer: PublicKey;
   escrowState: PublicKey;
+  mint: PublicKey;
+  delegate?: PublicKey;
   programId?: PublicKey;
 }): TransactionInstruction {
   const programId = params.programId ?? ESCROW_PROGRAM_ID;
@@ -279,8 +709,10 @@ export function buildFileDisputeIx(params: {
   return new TransactionInstruction({
     programId,
     keys: [
-      { pubkey: params.disputer, isSigner: true, isWritable: false },
+      { pubkey: params.delegate ?? params.disputer, isSigner: true, isWritable: false },
       { pubkey: params.escrowState, isSigner: false, isWritable: true },
+      { pubkey: findGlobalStatsPDA(params.mint, programId)[0], isSigner: false, isWritable: true },
+      sessionKeyMeta(params.disputer, params.delegate, programId, false),
     ],
     data: Buffer.from(DISCRIMINATORS.fileDispute),
   });
@@ -288,21 +720,30 @@ export function buildFileDisputeIx(params: {
 
 /**
  * Build a `resolve_dispute` instruction.
- * Admin resolves — releases to buyer or refunds to seller.
+ * The arbiter resolves — releases to beneficiary (`buyer`) or refunds to depositor (`seller`).
+ * Pass `destination` when the recipient chose a payout or refund account. A release pays
+ * the platform fee like `release_escrow`, so pass `feeRecipient` and the escrow's referrers.
  */
 export function buildResolveDisputeIx(params: {
-  admin: PublicKey;
+  arbiter: PublicKey;
   seller: PublicKey;
   buyer: PublicKey;
+  rentPayer?: PublicKey;
   mint: PublicKey;
   escrowState: PublicKey;
   resolution: Resolution;
+  destination?: PublicKey;
+  feeRecipient?: PublicKey;
+  sellerReferrer?: PublicKey;
+  buyerReferrer?: PublicKey;
   programId?: PublicKey;
 }): TransactionInstruction {
   const programId = params.programId ?? ESCROW_PROGRAM_ID;
   const [vault] = findVaultPDA(params.escrowState, programId);
+  const [config] = findConfigPDA(programId);
 
-  const recipient = params.resolution === Resolution.ReleaseToBuyer ? params.buyer : params.seller;
+  const recipient =
+    params.resolution === Resolution.ReleaseToBeneficiary ? params.buyer : params.seller;
   const recipientAta = getAssociatedTokenAddressSync(params.mint, recipient);
 
   // Serialize: discriminator + resolution enum (1 byte)
@@ -313,17 +754,21 @@ export function buildResolveDisputeIx(params: {
   return new TransactionInstruction({
     programId,
     keys: [
-      { pubkey: params.admin, isSigner: true, isWritable: true },
-      { pubkey: params.seller, isSigner: false, isWritable: true },
+      { pubkey: params.arbiter, isSigner: true, isWritable: true },
+      { pubkey: config, isSigner: false, isWritable: false },
+      { pubkey: params.rentPayer ?? params.seller, isSigner: false, isWritable: true },
       { pubkey: params.buyer, isSigner: false, isWritable: true },
       { pubkey: params.mint, isSigner: false, isWritable: false },
       { pubkey: params.escrowState, isSigner: false, isWritable: true },
+      { pubkey: findGlobalStatsPDA(params.mint, programId)[0], isSigner: false, isWritable: true },
       { pubkey: vault, isSigner: false, isWritable: true },
-      { pubkey: recipientAta, isSigner: false, isWritable: true },
+      optionalMeta(params.destination ? undefined : recipientAta, programId, true),
+      optionalMeta(params.destination, programId, true),
       { pubkey: recipient, isSigner: false, isWritable: false },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ...feeMetas(params, programId),
     ],
     data: Buffer.from(data),
   });
@@ -10,17 +10,38 @@ export {
   ESCROW_PROGRAM_ID,
   findEscrowPDA,
   findVaultPDA,
+  findSessionKeyPDA,
+  findRentSponsorPDA,
+  findConfigPDA,
+  findDenylistPDA,
+  findGlobalStatsPDA,
+  findReferrerStatsPDA,
+  findMintPolicyPDA,
+  findKycAttestationPDA,
+  findKycAttestorPDA,
+  findVolumeTrackerPDA,
   uuidToTradeId,
   tradeIdToUuid,
+  EscrowDirection,
   Resolution,
+  SessionPermission,
+  PaymentMethod,
   buildInitializeEscrowIx,
   buildMarkPaidIx,
   buildReleaseEscrowIx,
   buildRefundEscrowIx,
+  buildExtendTimeoutIx,
+  buildEmergencyWithdrawIx,
   buildFileDisputeIx,
   buildResolveDisputeIx,
 } from './escrow';
-export type { EscrowStatus, EscrowAccount } from './escrow';
+export type {
+  EscrowStatus,
+  EscrowAccount,
+  EscrowMode,
+  EscrowMilestone,
+  TradeMetadata,
+} from './escrow';
 
 // ============================================================================
 // Constants